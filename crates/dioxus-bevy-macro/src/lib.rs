@@ -1,12 +1,15 @@
 use proc_macro::TokenStream;
 use quote::{quote, format_ident};
-use syn::{parse_macro_input, ItemFn, ReturnType, FnArg, Pat, PatType};
+use syn::{parse_macro_input, ItemFn, ReturnType, FnArg, Pat, PatType, Type, GenericArgument, PathArguments};
+
+/// Props the macro adds to every component, unavailable as parameter names
+const RESERVED_PROPS: [&str; 6] = ["instance_key", "clear_color", "rebuild_on", "carry_over", "onpick", "onhover"];
 
 /// Transform a Bevy setup function into a Dioxus component
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,ignore
 /// #[bevy_component]
 /// fn triangle_scene(app: &mut App) {
 ///     app.add_systems(Startup, setup_triangle);
@@ -15,7 +18,7 @@ use syn::{parse_macro_input, ItemFn, ReturnType, FnArg, Pat, PatType};
 ///
 /// With signal props:
 ///
-/// ```rust
+/// ```rust,ignore
 /// #[bevy_component]
 /// fn gltf_scene(app: &mut App, light_enabled: ReadOnlySignal<bool>, speed: ReadOnlySignal<f32>) {
 ///     app.add_systems(Startup, setup_scene);
@@ -24,12 +27,92 @@ use syn::{parse_macro_input, ItemFn, ReturnType, FnArg, Pat, PatType};
 ///
 /// Generates a Dioxus component that can be used like:
 ///
-/// ```rust
+/// ```rust,ignore
 /// rsx! {
 ///     TriangleScene {}
 ///     GltfScene { light_enabled: my_signal, speed: speed_signal }
 /// }
 /// ```
+///
+/// Signal values are sent to the app's `SignalReceiver` under the prop name.
+/// Marking a prop `#[resource]` instead gives it a Bevy resource named after the
+/// component and the prop, kept up to date with the signal. The value type then
+/// has to be `Clone + PartialEq + Send + Sync`:
+///
+/// ```rust,ignore
+/// #[bevy_component]
+/// fn gltf_scene(app: &mut App, #[resource] speed: ReadSignal<f32>) {
+///     app.add_systems(Update, spin);
+/// }
+///
+/// // Generated: `GltfSceneSpeed`, replaced whenever the `speed` signal changes
+/// fn spin(speed: Res<GltfSceneSpeed>) {
+///     if speed.is_changed() {
+///         info!("speed is now {}", **speed);
+///     }
+/// }
+/// ```
+///
+/// Every generated component takes an optional `instance_key`. By default the
+/// Bevy app belongs to the component's scope; with a key it is identified by the
+/// key instead, so it survives remounts and components using the same key share it.
+/// Changing the key switches the component over to the app of the new key:
+///
+/// ```rust,ignore
+/// rsx! {
+///     GltfScene { instance_key: "preview", light_enabled: my_signal, speed: speed_signal }
+/// }
+/// ```
+///
+/// An optional `clear_color` sets the background behind the scene, e.g.
+/// `GltfScene { clear_color: Color::srgb(0.1, 0.1, 0.12) }`.
+///
+/// `rebuild_on` reruns the setup on a fresh app whenever its value changes,
+/// keeping the resources listed in `carry_over`. The new app gets the current
+/// value of every prop:
+///
+/// ```rust,ignore
+/// rsx! {
+///     GltfScene {
+///         rebuild_on: quality(),
+///         carry_over: BevyCarryOver::new().resource::<CameraRig>(),
+///         light_enabled: my_signal,
+///         speed: speed_signal,
+///     }
+/// }
+/// ```
+///
+/// `onpick` and `onhover` receive the entities clicked and hovered in the view,
+/// as on `BevyComponent`:
+///
+/// ```rust,ignore
+/// rsx! {
+///     GltfScene {
+///         onpick: move |pick: BevyPickEvent| selected.set(Some(pick.entity)),
+///         light_enabled: my_signal,
+///         speed: speed_signal,
+///     }
+/// }
+/// ```
+///
+/// These props are taken, so parameters can't be named `instance_key`,
+/// `clear_color`, `rebuild_on`, `carry_over`, `onpick` or `onhover`.
+///
+/// Writable `Signal<T>` props are sent like the others unless marked `#[bind]`,
+/// which binds them both ways to a resource:
+///
+/// ```rust,ignore
+/// #[bevy_component]
+/// fn gizmo_scene(app: &mut App, #[bind] offset: Signal<f32>) {
+///     app.add_systems(Update, drag_gizmo);
+/// }
+///
+/// // Generated: `GizmoSceneOffset`, a Bevy resource mirroring the signal.
+/// // Mutating it from a Bevy system writes the new value back to the signal.
+/// fn drag_gizmo(mut offset: ResMut<GizmoSceneOffset>) {
+///     offset.0 += 0.1;
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn bevy_component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemFn);
@@ -41,23 +124,46 @@ pub fn bevy_component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse function parameters
     let mut app_param = None;
     let mut signal_params = Vec::new();
+    let mut bound_params = Vec::new();
 
     for param in &input.sig.inputs {
-        if let FnArg::Typed(PatType { pat, ty, .. }) = param {
+        if let FnArg::Typed(PatType { attrs, pat, ty, .. }) = param {
             if let Pat::Ident(pat_ident) = &**pat {
                 let param_name = &pat_ident.ident;
                 let param_type = &**ty;
+                let as_resource = attrs.iter().any(|attr| attr.path().is_ident("resource"));
+                let bound = attrs.iter().any(|attr| attr.path().is_ident("bind"));
 
                 // First parameter should be `app: &mut App`
                 if app_param.is_none() {
                     app_param = Some(param_name.clone());
+                } else if RESERVED_PROPS.iter().any(|reserved| param_name == reserved) {
+                    let message = format!("`{param_name}` is a prop of every generated component; rename this parameter");
+                    return syn::Error::new_spanned(param_name, message).to_compile_error().into();
+                } else if bound {
+                    // Writable signals marked `#[bind]` are bound both ways to a resource
+                    let Some(value_type) = writable_signal_value(param_type) else {
+                        return syn::Error::new_spanned(param_type, "`#[bind]` props must be a `Signal<T>`")
+                            .to_compile_error()
+                            .into();
+                    };
+                    bound_params.push((param_name.clone(), param_type.clone(), value_type));
+                } else if as_resource {
+                    // Read-only signals marked `#[resource]` are mirrored one way into a resource
+                    let Some(value_type) = read_signal_value(param_type) else {
+                        return syn::Error::new_spanned(param_type, "`#[resource]` props must be a `ReadSignal<T>`")
+                            .to_compile_error()
+                            .into();
+                    };
+                    signal_params.push((param_name.clone(), param_type.clone(), Some(value_type)));
                 } else {
-                    // Other parameters are signals
-                    signal_params.push((param_name.clone(), param_type.clone()));
+                    // Other parameters are signals sent as `SignalUpdate`s
+                    signal_params.push((param_name.clone(), param_type.clone(), None));
                 }
             }
         }
     }
+    let app_ident = app_param.unwrap_or_else(|| format_ident!("app"));
 
     // Check if function returns something (for message handler)
     let has_message_handler = !matches!(input.sig.output, ReturnType::Default);
@@ -66,109 +172,195 @@ pub fn bevy_component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let component_name = to_pascal_case(&fn_name.to_string());
     let component_ident = format_ident!("{}", component_name);
 
-    // Generate props struct if we have signal parameters
-    let (props_def, component_params, prop_fields, use_effect_hooks) = if signal_params.is_empty() {
-        (quote! {}, quote! {}, quote! {}, quote! {})
-    } else {
-        let prop_names: Vec<_> = signal_params.iter().map(|(name, _)| name).collect();
-        let prop_types: Vec<_> = signal_params.iter().map(|(_, ty)| ty).collect();
+    // Signal and bound parameters become props
+    let all_params: Vec<_> = signal_params
+        .iter()
+        .map(|(name, ty, _)| (name.clone(), ty.clone()))
+        .chain(bound_params.iter().map(|(name, ty, _)| (name.clone(), ty.clone())))
+        .collect();
+    let prop_names: Vec<_> = all_params.iter().map(|(name, _)| name).collect();
+    let prop_types: Vec<_> = all_params.iter().map(|(_, ty)| ty).collect();
 
-        let props_struct_name = format_ident!("{}Props", component_name);
+    // Every component gets a props struct, as `instance_key` is always available.
+    // It is as visible as the component, like the generated prop resources
+    let props_struct_name = format_ident!("{}Props", component_name);
 
-        let props_def = quote! {
-            #[derive(Props, Clone, PartialEq)]
-            struct #props_struct_name {
-                #(#prop_names: #prop_types,)*
-            }
-        };
-
-        let component_params = quote! { props: #props_struct_name };
-
-        let prop_fields = quote! {
-            #(let #prop_names = props.#prop_names;)*
-        };
-
-        // Generate use_effect hooks to send signal updates to Bevy
-        // Each signal parameter gets its own use_effect that watches for changes
-        let use_effect_hooks = quote! {
-            #(
-                {
-                    let send_to_bevy = send_to_bevy.clone();
-                    let signal = #prop_names;
-                    use_effect(move || {
-                        let value = signal();
-                        send_to_bevy.send_signal_update(stringify!(#prop_names), value);
-                    });
-                }
-            )*
-        };
+    let props_def = quote! {
+        #[derive(Props, Clone, PartialEq)]
+        #fn_vis struct #props_struct_name {
+            /// Identity of the Bevy app; defaults to this component's scope.
+            /// Components given the same key share one app.
+            #[props(default, into)]
+            #fn_vis instance_key: Option<dioxus_bevy::BevyInstanceId>,
+            /// Background drawn where Bevy renders nothing; keeps the app's own
+            /// `ClearColor` when unset.
+            #[props(default, into)]
+            #fn_vis clear_color: Option<bevy::prelude::Color>,
+            /// Rebuilds the Bevy app from a fresh setup whenever it changes
+            #[props(default, into)]
+            #fn_vis rebuild_on: dioxus_bevy::RebuildKey,
+            /// Resources kept from the old app when it is rebuilt
+            #[props(default)]
+            #fn_vis carry_over: Option<dioxus_bevy::BevyCarryOver>,
+            /// Called when an entity in the Bevy view is clicked
+            #[props(default)]
+            #fn_vis onpick: Option<dioxus::prelude::EventHandler<dioxus_bevy::BevyPickEvent>>,
+            /// Called when the pointer starts or stops hovering an entity
+            #[props(default)]
+            #fn_vis onhover: Option<dioxus::prelude::EventHandler<dioxus_bevy::BevyPickEvent>>,
+            #(#fn_vis #prop_names: #prop_types,)*
+        }
+    };
+
+    let component_signature = quote! { props: #props_struct_name };
 
-        (props_def, component_params, prop_fields, use_effect_hooks)
+    let prop_fields = quote! {
+        #(let #prop_names = props.#prop_names;)*
     };
 
-    let component_signature = if signal_params.is_empty() {
-        quote! {}
-    } else {
-        component_params
+    // Generate use_effect hooks to send signal updates to Bevy
+    // Each signal parameter without a resource gets its own use_effect that watches for changes
+    let signal_names: Vec<_> = signal_params
+        .iter()
+        .filter(|(_, _, resource)| resource.is_none())
+        .map(|(name, _, _)| name)
+        .collect();
+    let use_effect_hooks = quote! {
+        #(
+            {
+                let signal = #signal_names;
+                // Also sent again when the instance changes, so a new app starts with the value
+                use_effect(use_reactive((&send_to_bevy,), move |(send_to_bevy,)| {
+                    use dioxus_bevy::__private::{IntoScalarUpdate, IntoValueUpdate, SignalUpdateOf};
+                    // Scalars keep their typed variant, other types go through `SignalUpdate::Value`
+                    let update = (&&SignalUpdateOf(signal())).into_update(stringify!(#signal_names));
+                    send_to_bevy.send(Box::new(update));
+                }));
+            }
+        )*
     };
 
-    let expanded = if has_message_handler {
-        // Function returns a message handler (not implemented yet)
-        quote! {
-            #props_def
-
-            #[allow(non_snake_case)]
-            #fn_vis fn #component_ident(#component_signature) -> dioxus::prelude::Element {
-                use dioxus::prelude::*;
-                use dioxus_core::current_scope_id;
-                use dioxus_bevy::{BevyComponent, BevyAppRenderer};
-                use std::sync::Arc;
-
-                #prop_fields
-
-                let instance_id = current_scope_id();
-                let send_to_bevy = dioxus_bevy::use_bevy_message(instance_id);
-                #use_effect_hooks
-
-                rsx! {
-                    BevyComponent {
-                        instance_id,
-                        factory: Arc::new(|device| {
-                            Box::new(BevyAppRenderer::new(device, |app| {
-                                let handler = (|| #fn_body)();
-                                // TODO: Store handler for later use
-                                #fn_body
-                            }))
-                        }),
-                    }
+    // Generate a resource type per signal prop, named after the component and the prop.
+    // They are plain items next to the component, so components declared inside a
+    // function body work too.
+    let resource_ident = |name: &syn::Ident| format_ident!("{}{}", component_name, to_pascal_case(&name.to_string()));
+    let mirrored_params: Vec<_> = signal_params
+        .iter()
+        .filter_map(|(name, _, value)| value.clone().map(|value| (name.clone(), value)))
+        .collect();
+    let prop_resources: Vec<_> = mirrored_params
+        .iter()
+        .map(|(name, value)| (name, value, format!("Bevy resource mirroring the `{}` prop of `{}`", name, component_name)))
+        .chain(bound_params.iter().map(|(name, _, value)| {
+            (name, value, format!("Bevy resource bound to the `{}` prop of `{}`", name, component_name))
+        }))
+        .collect();
+    let resource_names: Vec<_> = prop_resources.iter().map(|(name, _, _)| *name).collect();
+    let resource_value_types: Vec<_> = prop_resources.iter().map(|(_, value, _)| *value).collect();
+    let resource_docs: Vec<_> = prop_resources.iter().map(|(_, _, doc)| doc).collect();
+    let resource_types: Vec<_> = resource_names.iter().map(|name| resource_ident(name)).collect();
+
+    let resources_def = quote! {
+        #(
+            #[doc = #resource_docs]
+            #[derive(bevy::prelude::Resource, bevy::prelude::Deref, bevy::prelude::DerefMut, Clone, PartialEq)]
+            #fn_vis struct #resource_types(pub #resource_value_types);
+
+            impl dioxus_bevy::BevyProp for #resource_types {
+                type Value = #resource_value_types;
+
+                fn from_value(value: Self::Value) -> Self {
+                    Self(value)
+                }
+
+                fn into_value(self) -> Self::Value {
+                    self.0
                 }
             }
-        }
+        )*
+    };
+
+    // Mirror signals into their resources, keeping their latest values for the factory
+    let mirrored_names: Vec<_> = mirrored_params.iter().map(|(name, _)| name).collect();
+    let mirrored_resources: Vec<_> = mirrored_names.iter().map(|name| resource_ident(name)).collect();
+    let mirrored_latest: Vec<_> = mirrored_names
+        .iter()
+        .map(|name| format_ident!("__latest_{}", name))
+        .collect();
+    let bound_names: Vec<_> = bound_params.iter().map(|(name, _, _)| name).collect();
+    let bound_resources: Vec<_> = bound_names.iter().map(|name| resource_ident(name)).collect();
+    let bound_latest: Vec<_> = bound_names
+        .iter()
+        .map(|name| format_ident!("__latest_{}", name))
+        .collect();
+
+    let binding_hooks = quote! {
+        #(
+            let #mirrored_latest = dioxus_bevy::use_bevy_prop::<#mirrored_resources>(instance_id.clone(), #mirrored_names);
+        )*
+        #(
+            let #bound_latest = dioxus_bevy::use_bevy_binding::<#bound_resources>(instance_id.clone(), #bound_names);
+        )*
+    };
+
+    // Inserts every prop resource before user setup, so systems can take `Res<...>` directly
+    let insert_props = quote! {
+        #(#app_ident.insert_resource(#mirrored_resources(#mirrored_latest));)*
+        #(dioxus_bevy::bind_prop(#app_ident, #bound_resources(#bound_latest));)*
+    };
+    let latest: Vec<_> = mirrored_latest.iter().chain(bound_latest.iter()).collect();
+
+    // A returned message handler isn't stored yet; the setup still runs once
+    let setup = if has_message_handler {
+        quote! { let _handler = #fn_body; }
     } else {
-        // Function only sets up Bevy app
-        quote! {
-            #props_def
-
-            #[allow(non_snake_case)]
-            #fn_vis fn #component_ident(#component_signature) -> dioxus::prelude::Element {
-                use dioxus::prelude::*;
-                use dioxus_core::current_scope_id;
-                use dioxus_bevy::{BevyComponent, BevyAppRenderer};
-                use std::sync::Arc;
-
-                #prop_fields
-
-                let instance_id = current_scope_id();
-                let send_to_bevy = dioxus_bevy::use_bevy_message(instance_id);
-                #use_effect_hooks
-
-                rsx! {
-                    BevyComponent {
-                        instance_id,
-                        factory: Arc::new(|device| {
-                            Box::new(BevyAppRenderer::new(device, |app| #fn_body))
-                        }),
-                    }
+        quote! { #fn_body }
+    };
+    let factory = quote! {
+        Arc::new(move |device| {
+            // Apps rebuilt later start from the props' values at that time
+            #(let #latest = #latest.lock().unwrap().clone();)*
+            Box::new(BevyAppRenderer::new(device, move |#app_ident| {
+                #insert_props
+                #setup
+            }))
+        })
+    };
+
+    let expanded = quote! {
+        #props_def
+
+        #resources_def
+
+        #[allow(non_snake_case)]
+        #fn_vis fn #component_ident(#component_signature) -> dioxus::prelude::Element {
+            use dioxus::prelude::*;
+            use dioxus_core::current_scope_id;
+            use dioxus_bevy::{BevyComponent, BevyAppRenderer};
+            use std::sync::Arc;
+
+            let clear_color = props.clear_color;
+            let rebuild_on = props.rebuild_on;
+            let carry_over = props.carry_over;
+            let (onpick, onhover) = (props.onpick, props.onhover);
+            let instance_id = props
+                .instance_key
+                .unwrap_or_else(|| dioxus_bevy::BevyInstanceId::from(current_scope_id()));
+            #prop_fields
+
+            let send_to_bevy = dioxus_bevy::use_bevy_message(instance_id.clone());
+            #use_effect_hooks
+            #binding_hooks
+
+            rsx! {
+                BevyComponent {
+                    instance_id,
+                    clear_color,
+                    rebuild_on,
+                    carry_over,
+                    onpick,
+                    onhover,
+                    factory: #factory,
                 }
             }
         }
@@ -177,6 +369,36 @@ pub fn bevy_component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Extract `T` from a writable `Signal<T>` parameter type
+///
+/// Read-only signals (`ReadSignal`, `ReadOnlySignal`) and plain values return `None`.
+fn writable_signal_value(ty: &Type) -> Option<Type> {
+    signal_value(ty, &["Signal"])
+}
+
+/// Extract `T` from a read-only `ReadSignal<T>` or `ReadOnlySignal<T>` parameter type
+fn read_signal_value(ty: &Type) -> Option<Type> {
+    signal_value(ty, &["ReadSignal", "ReadOnlySignal"])
+}
+
+/// Extract the first type argument of a signal type whose name is one of `wrappers`
+fn signal_value(ty: &Type, wrappers: &[&str]) -> Option<Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if !wrappers.iter().any(|wrapper| segment.ident == wrapper) {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(value_type) => Some(value_type.clone()),
+        _ => None,
+    })
+}
+
 /// Convert snake_case to PascalCase
 fn to_pascal_case(s: &str) -> String {
     s.split('_')
@@ -0,0 +1,421 @@
+//! Dioxus elements anchored to Bevy entities
+//!
+//! A `BevyAnchor` in a `BevyComponent`'s children registers its entity and
+//! offset with the app. After transforms propagate, a Bevy system projects every
+//! anchor through the canvas camera, drops the ones behind the camera (and,
+//! with `AnchorOcclusion`, the ones hidden behind meshes), and sends the screen
+//! positions back; the anchors move their elements there.
+
+use bevy::app::{App, Plugin, PostUpdate};
+use bevy::camera::{Camera, RenderTarget};
+use bevy::ecs::entity::Entity;
+use bevy::ecs::hierarchy::ChildOf;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::schedule::IntoScheduleConfigs;
+use bevy::ecs::system::{Local, Query, Res};
+use bevy::ecs::world::World;
+use bevy::math::{Dir3, Ray3d, URect, Vec2, Vec3};
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings};
+use bevy::transform::components::GlobalTransform;
+use bevy::transform::TransformSystems;
+use dioxus::prelude::*;
+use dioxus_core::{use_drop, Task};
+use futures_util::StreamExt;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::camera::CanvasTarget;
+use crate::events::DioxusEvents;
+use crate::window::CanvasViewport;
+use crate::{BevyInstanceId, BevyInstanceManager, BevyRenderer, WorldUpdate};
+
+/// Identifies an anchor within its app
+type AnchorId = u64;
+
+static NEXT_ANCHOR_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Entity and world-space offset of each anchor
+type AnchorTargets = HashMap<AnchorId, (Entity, Vec3)>;
+
+/// Anchors the app projects every frame
+#[derive(Resource, Default)]
+struct TrackedAnchors(AnchorTargets);
+
+/// Hide `BevyAnchor`s behind other meshes
+///
+/// Insert it as a resource during setup. Without it anchors only hide behind
+/// the camera or off its depth range. Occlusion casts a ray through the scene
+/// for every anchor each frame, and needs the app to have mesh assets.
+///
+/// # Example
+/// ```rust,ignore
+/// app.insert_resource(AnchorOcclusion);
+/// ```
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct AnchorOcclusion;
+
+/// Positions of the visible anchors, in CSS pixels from the canvas' top-left corner
+#[derive(Clone, Default, PartialEq)]
+struct AnchorFrame(Arc<HashMap<AnchorId, Vec2>>);
+
+/// Projects anchors and reports their screen positions to Dioxus
+pub(crate) struct AnchorPlugin;
+
+impl Plugin for AnchorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TrackedAnchors>()
+            .add_systems(PostUpdate, project_anchors.after(TransformSystems::Propagate));
+    }
+}
+
+/// Send the screen positions of the tracked anchors, when they changed
+#[allow(clippy::too_many_arguments)]
+fn project_anchors(
+    anchors: Res<TrackedAnchors>,
+    target: Res<CanvasTarget>,
+    viewport: Res<CanvasViewport>,
+    events: Res<DioxusEvents>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    transforms: Query<&GlobalTransform>,
+    parents: Query<&ChildOf>,
+    occlusion: Option<Res<AnchorOcclusion>>,
+    ray_cast: Option<MeshRayCast>,
+    mut last_frame: Local<AnchorFrame>,
+) {
+    let mut positions = HashMap::new();
+    let mut ray_cast = occlusion.and(ray_cast);
+
+    // The canvas camera drawn first is the one showing the scene; later ones
+    // are usually overlays
+    let camera = target.view.and_then(|view| {
+        cameras
+            .iter()
+            .filter(|(camera, _)| camera.is_active)
+            .filter(|(camera, _)| matches!(camera.target, RenderTarget::TextureView(handle) if handle == view))
+            .min_by_key(|(camera, _)| camera.order)
+    });
+
+    if let Some((camera, camera_transform)) = camera {
+        for (id, (entity, offset)) in &anchors.0 {
+            let Ok(transform) = transforms.get(*entity) else {
+                continue;
+            };
+            let point = transform.translation() + *offset;
+            let Some(position) = canvas_position(camera, camera_transform, point) else {
+                continue;
+            };
+            if let Some(ray_cast) = &mut ray_cast {
+                if occluded(ray_cast, &parents, camera_transform.translation(), point, *entity) {
+                    continue;
+                }
+            }
+            positions.insert(*id, position / viewport.scale_factor);
+        }
+    }
+
+    let frame = AnchorFrame(Arc::new(positions));
+    if *last_frame != frame {
+        events.send(frame.clone());
+        *last_frame = frame;
+    }
+}
+
+/// Position of `point` on the canvas, in physical pixels from its top-left corner
+///
+/// `None` for points behind the camera or outside its depth range.
+fn canvas_position(camera: &Camera, camera_transform: &GlobalTransform, point: Vec3) -> Option<Vec2> {
+    let ndc = camera.world_to_ndc(camera_transform, point)?;
+    ndc_to_canvas(ndc, camera.physical_viewport_rect()?)
+}
+
+/// Map normalized device coordinates into the camera's viewport on the canvas
+fn ndc_to_canvas(ndc: Vec3, viewport: URect) -> Option<Vec2> {
+    if !(0.0..=1.0).contains(&ndc.z) {
+        return None;
+    }
+    // NDC y points up, canvas y down
+    let uv = (Vec2::new(ndc.x, -ndc.y) + Vec2::ONE) / 2.0;
+    Some(viewport.min.as_vec2() + uv * viewport.size().as_vec2())
+}
+
+/// Whether a mesh other than the anchored entity's own lies between the camera and `point`
+fn occluded(
+    ray_cast: &mut MeshRayCast,
+    parents: &Query<&ChildOf>,
+    from: Vec3,
+    point: Vec3,
+    anchored: Entity,
+) -> bool {
+    let Ok(direction) = Dir3::new(point - from) else {
+        return false;
+    };
+    let distance = from.distance(point);
+
+    // The anchored entity's meshes, e.g. the children of a scene root, don't hide it
+    let is_anchored = |mut entity: Entity| loop {
+        if entity == anchored {
+            return true;
+        }
+        match parents.get(entity) {
+            Ok(child_of) => entity = child_of.parent(),
+            Err(_) => return false,
+        }
+    };
+    let filter = |entity: Entity| !is_anchored(entity);
+    let settings = MeshRayCastSettings::default()
+        .with_filter(&filter)
+        .always_early_exit();
+
+    ray_cast
+        .cast_ray(Ray3d::new(from, direction), &settings)
+        .first()
+        .is_some_and(|(_, hit)| hit.distance < distance * 0.999)
+}
+
+/// Anchors of a `BevyComponent`, kept on the Dioxus side so apps rebuilt for
+/// the component get them again
+pub(crate) type AnchorRegistry = Arc<Mutex<AnchorTargets>>;
+
+/// Register every anchor of `registry` with a newly built renderer
+pub(crate) fn restore_anchors(renderer: &mut dyn BevyRenderer, registry: &AnchorRegistry) {
+    let anchors = registry.lock().unwrap().clone();
+    if anchors.is_empty() {
+        return;
+    }
+    renderer.handle_message(Box::new(WorldUpdate(Box::new(move |world: &mut World| {
+        if let Some(mut tracked) = world.get_resource_mut::<TrackedAnchors>() {
+            tracked.0.extend(anchors);
+        }
+    }))));
+}
+
+/// Overlay of a `BevyComponent`, as seen by the anchors in it
+#[derive(Clone)]
+pub(crate) struct AnchorLayer {
+    instance_id: Signal<BevyInstanceId>,
+    registry: AnchorRegistry,
+    positions: Signal<Arc<HashMap<AnchorId, Vec2>>>,
+}
+
+/// Provide the anchor layer of a `BevyComponent` to its children
+///
+/// Keeps the anchor positions up to date with the frames Bevy sends.
+pub(crate) fn use_anchor_layer(
+    manager: Signal<BevyInstanceManager>,
+    instance_id: BevyInstanceId,
+    registry: AnchorRegistry,
+) {
+    let positions = use_signal(|| Arc::new(HashMap::new()));
+    let mut current_id = use_signal(|| instance_id.clone());
+    if *current_id.peek() != instance_id {
+        current_id.set(instance_id.clone());
+    }
+    use_context_provider(|| AnchorLayer {
+        instance_id: current_id,
+        registry,
+        positions,
+    });
+
+    // Follow the frames of the current instance, switching over when it changes
+    let frames_task = use_hook(|| Rc::new(Cell::new(None::<Task>)));
+    use_effect(use_reactive((&instance_id,), move |(instance_id,)| {
+        let mut frames = manager.peek().event_bus(&instance_id).subscribe::<AnchorFrame>();
+        let mut positions = positions;
+        let task = spawn(async move {
+            while let Some(frame) = frames.next().await {
+                positions.set(frame.0);
+            }
+        });
+        if let Some(previous) = frames_task.replace(Some(task)) {
+            previous.cancel();
+        }
+    }));
+}
+
+/// Have the app project an anchor
+fn track_anchor(id: AnchorId, entity: Entity, offset: Vec3) -> WorldUpdate {
+    WorldUpdate(Box::new(move |world: &mut World| {
+        if let Some(mut tracked) = world.get_resource_mut::<TrackedAnchors>() {
+            tracked.0.insert(id, (entity, offset));
+        }
+    }))
+}
+
+/// Have the app stop projecting an anchor
+fn untrack_anchor(id: AnchorId) -> WorldUpdate {
+    WorldUpdate(Box::new(move |world: &mut World| {
+        if let Some(mut tracked) = world.get_resource_mut::<TrackedAnchors>() {
+            tracked.0.remove(&id);
+        }
+    }))
+}
+
+/// Props for BevyAnchor
+#[derive(Props, Clone, PartialEq)]
+pub struct BevyAnchorProps {
+    /// Entity to follow
+    pub entity: Entity,
+
+    /// World-space offset from the entity's position
+    #[props(default)]
+    pub offset: Vec3,
+
+    /// Content shown at the entity
+    pub children: Element,
+}
+
+/// Element following a Bevy entity across the canvas
+///
+/// Must be placed in the children of a `BevyComponent`. Its top-left corner
+/// sits on the projection of the entity's position plus `offset`, through the
+/// first active camera drawing the canvas. It is hidden while that point is
+/// behind the camera or off its depth range, and, if the app has the
+/// `AnchorOcclusion` resource, while it is behind another mesh.
+///
+/// Style the content to place it around the point, e.g. with
+/// `transform: translate(-50%, -100%)` to center it above.
+///
+/// # Example
+/// ```rust,ignore
+/// BevyComponent {
+///     instance_id,
+///     factory,
+///     for (entity, name) in players() {
+///         BevyAnchor {
+///             key: "{entity}",
+///             entity,
+///             offset: Vec3::Y * 2.0,
+///             div { class: "nameplate", "{name}" }
+///         }
+///     }
+/// }
+/// ```
+#[component]
+pub fn BevyAnchor(props: BevyAnchorProps) -> Element {
+    let BevyAnchorProps {
+        entity,
+        offset,
+        children,
+    } = props;
+    let manager = crate::use_instance_manager();
+    let layer = use_context::<AnchorLayer>();
+    let id = use_hook(|| NEXT_ANCHOR_ID.fetch_add(1, Ordering::Relaxed));
+    let instance_id = layer.instance_id.read().clone();
+
+    // Register with the app, again whenever the app, entity or offset changes
+    let registered = use_hook(|| Rc::new(RefCell::new(None::<BevyInstanceId>)));
+    use_effect(use_reactive((&instance_id, &entity, &offset), {
+        let registry = layer.registry.clone();
+        let registered = registered.clone();
+        move |(instance_id, entity, offset)| {
+            registry.lock().unwrap().insert(id, (entity, offset));
+            let previous = registered.replace(Some(instance_id.clone()));
+            if let Some(previous) = previous.filter(|previous| *previous != instance_id) {
+                manager.peek().send_message(&previous, Box::new(untrack_anchor(id)));
+            }
+            manager.peek().send_message(&instance_id, Box::new(track_anchor(id, entity, offset)));
+        }
+    }));
+
+    use_drop({
+        let registry = layer.registry.clone();
+        move || {
+            registry.lock().unwrap().remove(&id);
+            if let Some(instance_id) = registered.take() {
+                manager.peek().send_message(&instance_id, Box::new(untrack_anchor(id)));
+            }
+        }
+    });
+
+    let positions = layer.positions;
+    let position = use_memo(move || positions.read().get(&id).copied());
+
+    // Hidden rather than unmounted, so the content keeps its state
+    let style = match position() {
+        Some(position) => format!("position: absolute; left: {}px; top: {}px;", position.x, position.y),
+        None => "position: absolute; display: none;".to_string(),
+    };
+    rsx! {
+        div {
+            style,
+            {children}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{render, root_manager, run_tasks, unbuilt_factory};
+    use crate::BevyComponent;
+    use bevy::math::UVec2;
+
+    #[test]
+    fn projected_points_are_offset_by_the_camera_viewport() {
+        let viewport = URect::from_corners(UVec2::new(100, 50), UVec2::new(300, 150));
+        assert_eq!(ndc_to_canvas(Vec3::new(0.0, 0.0, 0.5), viewport), Some(Vec2::new(200.0, 100.0)));
+        assert_eq!(ndc_to_canvas(Vec3::new(-1.0, 1.0, 0.5), viewport), Some(Vec2::new(100.0, 50.0)));
+        assert_eq!(ndc_to_canvas(Vec3::new(1.0, -1.0, 0.5), viewport), Some(Vec2::new(300.0, 150.0)));
+    }
+
+    #[test]
+    fn points_outside_the_depth_range_are_not_projected() {
+        let viewport = URect::from_corners(UVec2::ZERO, UVec2::new(200, 100));
+        assert_eq!(ndc_to_canvas(Vec3::new(0.0, 0.0, -0.1), viewport), None);
+        assert_eq!(ndc_to_canvas(Vec3::new(0.0, 0.0, 1.1), viewport), None);
+    }
+
+    #[test]
+    fn anchors_register_once_and_unregister_on_unmount() {
+        fn app() -> Element {
+            let shown = use_context_provider(|| Signal::new(true));
+            let frame = use_context_provider(|| Signal::new(0));
+            rsx! {
+                BevyComponent { instance_id: "scene", factory: unbuilt_factory(),
+                    if shown() {
+                        BevyAnchor { entity: Entity::from_raw_u32(7).unwrap(), offset: Vec3::Y,
+                            "frame {frame}"
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut dom = render(app);
+        run_tasks(&mut dom);
+        let manager = root_manager(&dom);
+        let scene = BevyInstanceId::keyed("scene");
+        let mut world = World::new();
+        world.init_resource::<TrackedAnchors>();
+        let apply_sent = |world: &mut World| {
+            let pending = manager.inner.lock().unwrap().pending_messages.remove(&scene).unwrap_or_default();
+            let mut applied = 0;
+            for msg in pending {
+                if let Ok(update) = msg.downcast::<WorldUpdate>() {
+                    (update.0)(world);
+                    applied += 1;
+                }
+            }
+            applied
+        };
+        apply_sent(&mut world);
+        let tracked: Vec<_> = world.resource::<TrackedAnchors>().0.values().copied().collect();
+        assert_eq!(tracked, vec![(Entity::from_raw_u32(7).unwrap(), Vec3::Y)]);
+
+        let (mut shown, mut frame) =
+            dom.in_scope(ScopeId::APP, || (consume_context::<Signal<bool>>(), consume_context::<Signal<i32>>()));
+        dom.in_scope(ScopeId::APP, || frame.set(1));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+        assert_eq!(apply_sent(&mut world), 0);
+
+        dom.in_scope(ScopeId::APP, || shown.set(false));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+        apply_sent(&mut world);
+        assert!(world.resource::<TrackedAnchors>().0.is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Cameras rendering into the canvas
+//!
+//! The canvas is a `ManualTextureView` owned by `BevyAppRenderer`. Every camera
+//! feeding it is pointed at that view, and Bevy composites them by `order`.
+//! Cameras with any other explicit target (an `Image`, their own texture view)
+//! are left alone.
+//!
+//! Cameras spawned, replaced or marked after the view exists are attached as
+//! they appear, by observers on `Camera` and `CanvasCamera`. Removing either
+//! has every camera looked at again.
+
+use bevy::app::{App, Plugin};
+use bevy::camera::{Camera, ManualTextureViewHandle, RenderTarget};
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::lifecycle::{Insert, Remove};
+use bevy::ecs::observer::On;
+use bevy::ecs::query::{Has, With};
+use bevy::ecs::resource::Resource;
+use bevy::ecs::system::{Commands, Query, Res};
+use bevy::ecs::world::World;
+use bevy::window::WindowRef;
+
+/// Texture view handle of the canvas render target
+///
+/// Reserved by `BevyAppRenderer`; pick other handles for your own
+/// `ManualTextureViews`.
+pub const CANVAS_TEXTURE_VIEW: ManualTextureViewHandle = ManualTextureViewHandle(u32::MAX);
+
+/// Marks a camera that renders into the canvas
+///
+/// Without any marked camera, every camera targeting the primary window (the
+/// default target) renders into the canvas. Once one camera is marked, only
+/// marked cameras do.
+///
+/// Cameras sharing the canvas are drawn in `Camera::order`; give the later ones
+/// `ClearColorConfig::None` so they draw over the earlier ones.
+///
+/// # Example
+/// ```rust,ignore
+/// commands.spawn((Camera3d::default(), CanvasCamera));
+/// commands.spawn((
+///     Camera2d,
+///     Camera { order: 1, clear_color: ClearColorConfig::None, ..default() },
+///     CanvasCamera,
+/// ));
+/// ```
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct CanvasCamera;
+
+/// Canvas texture view cameras are attached to
+#[derive(Resource)]
+pub(crate) struct CanvasTarget {
+    /// Virtual window standing in for the canvas
+    pub(crate) window: Entity,
+    /// The canvas texture view, while it exists
+    pub(crate) view: Option<ManualTextureViewHandle>,
+}
+
+/// Keeps cameras spawned after the canvas texture attached to it
+pub(crate) struct CanvasCameraPlugin {
+    pub(crate) window: Entity,
+}
+
+impl Plugin for CanvasCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CanvasTarget {
+            window: self.window,
+            view: None,
+        })
+        .add_observer(attach_inserted_camera::<Camera>)
+        .add_observer(attach_inserted_camera::<CanvasCamera>)
+        .add_observer(reattach_cameras_on_remove::<Camera>)
+        .add_observer(reattach_cameras_on_remove::<CanvasCamera>);
+    }
+}
+
+/// Attach a camera to the canvas when it is spawned, replaced or marked
+///
+/// Marking a camera can take the canvas away from the unmarked ones, so every
+/// camera is looked at again. Before the texture view exists there is nothing
+/// to attach to; creating it attaches every camera at once.
+fn attach_inserted_camera<C: Component>(
+    _insert: On<Insert, C>,
+    target: Res<CanvasTarget>,
+    mut cameras: Query<(&mut Camera, Has<CanvasCamera>)>,
+) {
+    let Some(handle) = target.view else {
+        return;
+    };
+    let any_marked = cameras.iter().any(|(_, marked)| marked);
+    for (mut camera, marked) in &mut cameras {
+        if let Some(new_target) = canvas_target(&camera, marked, any_marked, target.window, handle) {
+            camera.target = new_target;
+        }
+    }
+}
+
+/// Look at every camera again when a camera or its mark is removed or despawned
+///
+/// Once the last marked camera is gone, the unmarked ones get the canvas back.
+/// The component is still there while observers run, so the cameras are
+/// attached after the removal.
+fn reattach_cameras_on_remove<C: Component>(
+    _remove: On<Remove, C>,
+    target: Res<CanvasTarget>,
+    mut commands: Commands,
+) {
+    let Some(handle) = target.view else {
+        return;
+    };
+    let window = target.window;
+    commands.queue(move |world: &mut World| attach_canvas_cameras(world, window, handle));
+}
+
+/// Point every camera feeding the canvas at its texture view, and the others away from it
+pub(crate) fn attach_canvas_cameras(
+    world: &mut World,
+    window: Entity,
+    handle: ManualTextureViewHandle,
+) {
+    let any_marked = world
+        .query_filtered::<(), (With<Camera>, With<CanvasCamera>)>()
+        .iter(world)
+        .next()
+        .is_some();
+
+    let mut cameras = world.query::<(&mut Camera, Has<CanvasCamera>)>();
+    for (mut camera, marked) in cameras.iter_mut(world) {
+        if let Some(new_target) = canvas_target(&camera, marked, any_marked, window, handle) {
+            camera.target = new_target;
+        }
+    }
+}
+
+/// New target of a camera, if it should start or stop rendering into the canvas
+///
+/// Unmarked cameras on the canvas view are moved back to the default target
+/// once another camera is marked.
+fn canvas_target(
+    camera: &Camera,
+    marked: bool,
+    any_marked: bool,
+    window: Entity,
+    handle: ManualTextureViewHandle,
+) -> Option<RenderTarget> {
+    let on_canvas = matches!(camera.target, RenderTarget::TextureView(target) if target == handle);
+    let on_window = matches!(
+        camera.target,
+        RenderTarget::Window(WindowRef::Primary)
+    ) || matches!(
+        camera.target,
+        RenderTarget::Window(WindowRef::Entity(entity)) if entity == window
+    );
+
+    let feeds_canvas = if any_marked { marked } else { on_canvas || on_window };
+    match (feeds_canvas, on_canvas) {
+        (true, false) => Some(RenderTarget::TextureView(handle)),
+        (false, true) => Some(RenderTarget::default()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::Handle;
+    use bevy::image::Image;
+
+    const WINDOW: Entity = Entity::from_raw_u32(7).unwrap();
+
+    fn retarget(target: RenderTarget, marked: bool, any_marked: bool) -> Option<RenderTarget> {
+        let camera = Camera {
+            target,
+            ..Camera::default()
+        };
+        canvas_target(&camera, marked, any_marked, WINDOW, CANVAS_TEXTURE_VIEW)
+    }
+
+    fn is_canvas(target: Option<RenderTarget>) -> bool {
+        matches!(target, Some(RenderTarget::TextureView(handle)) if handle == CANVAS_TEXTURE_VIEW)
+    }
+
+    #[test]
+    fn window_cameras_feed_the_canvas_until_one_is_marked() {
+        let virtual_window = || RenderTarget::Window(WindowRef::Entity(WINDOW));
+
+        assert!(is_canvas(retarget(RenderTarget::default(), false, false)));
+        assert!(is_canvas(retarget(virtual_window(), false, false)));
+        assert!(retarget(RenderTarget::default(), false, true).is_none());
+        assert!(is_canvas(retarget(RenderTarget::default(), true, true)));
+    }
+
+    #[test]
+    fn unmarked_canvas_cameras_are_detached_once_one_is_marked() {
+        let on_canvas = || RenderTarget::TextureView(CANVAS_TEXTURE_VIEW);
+
+        assert!(retarget(on_canvas(), false, false).is_none());
+        assert!(retarget(on_canvas(), true, true).is_none());
+        assert!(matches!(
+            retarget(on_canvas(), false, true),
+            Some(RenderTarget::Window(WindowRef::Primary))
+        ));
+    }
+
+    #[test]
+    fn removing_the_last_mark_gives_the_canvas_back() {
+        let mut world = World::new();
+        world.insert_resource(CanvasTarget {
+            window: WINDOW,
+            view: Some(CANVAS_TEXTURE_VIEW),
+        });
+        world.add_observer(attach_inserted_camera::<Camera>);
+        world.add_observer(attach_inserted_camera::<CanvasCamera>);
+        world.add_observer(reattach_cameras_on_remove::<Camera>);
+        world.add_observer(reattach_cameras_on_remove::<CanvasCamera>);
+        let on_canvas = |world: &World, entity| is_canvas(Some(world.get::<Camera>(entity).unwrap().target.clone()));
+
+        let unmarked = world.spawn(Camera::default()).id();
+        let marked = world.spawn((Camera::default(), CanvasCamera)).id();
+        assert!(!on_canvas(&world, unmarked));
+        assert!(on_canvas(&world, marked));
+
+        world.entity_mut(marked).remove::<CanvasCamera>();
+        world.flush();
+        assert!(on_canvas(&world, unmarked));
+
+        let marked = world.spawn((Camera::default(), CanvasCamera)).id();
+        assert!(!on_canvas(&world, unmarked));
+        world.despawn(marked);
+        world.flush();
+        assert!(on_canvas(&world, unmarked));
+    }
+
+    #[test]
+    fn cameras_with_other_targets_are_left_alone() {
+        for any_marked in [false, true] {
+            assert!(retarget(Handle::<Image>::default().into(), false, any_marked).is_none());
+            assert!(retarget(RenderTarget::TextureView(ManualTextureViewHandle(3)), false, any_marked).is_none());
+        }
+    }
+}
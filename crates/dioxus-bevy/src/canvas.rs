@@ -0,0 +1,177 @@
+//! Canvas element showing a Bevy paint source
+//!
+//! Shared by `BevyComponent` and `BevyViewport`: displays the paint source and
+//! turns mouse, touch, keyboard and focus events over it into `BevyInputEvent`s,
+//! showing the cursor Bevy asks for. While the pointer is locked, a layer over
+//! the whole window keeps reporting mouse motion past the canvas' edges.
+//! Elements over the canvas go in a `CanvasOverlay` next to it, so their events
+//! never reach the canvas handlers.
+
+use bevy::input::touch::TouchPhase;
+use bevy::input::ButtonState;
+use bevy::math::Vec2;
+use dioxus::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::input::{self, BevyInputEvent};
+
+/// Stylesheet of the overlay layer
+///
+/// The layer covers the canvas but lets pointer events through to it; its
+/// direct children take them back, so a click on a HUD button stays out of Bevy.
+const OVERLAY_STYLE: &str = "
+.dioxus-bevy-overlay {
+    position: absolute;
+    top: 0;
+    left: 0;
+    right: 0;
+    bottom: 0;
+    overflow: hidden;
+    pointer-events: none;
+}
+.dioxus-bevy-overlay > * {
+    pointer-events: auto;
+}
+";
+
+/// Style of the layer catching a locked pointer's motion over the whole window
+const LOCK_LAYER_STYLE: &str =
+    "position: fixed; top: 0; left: 0; right: 0; bottom: 0; z-index: 2147483647; cursor: none;";
+
+/// How long after a touch mouse events are taken for its compatibility events
+///
+/// Long enough for the ones browsers send after lifting a finger, short
+/// enough that a mouse used right after works, even without pointer events.
+const COMPAT_MOUSE_WINDOW: Duration = Duration::from_millis(800);
+
+/// Positioned layer laid over a canvas
+///
+/// Must be placed in a positioned container together with the canvas.
+#[component]
+pub(crate) fn CanvasOverlay(children: Element) -> Element {
+    rsx! {
+        style { {OVERLAY_STYLE} }
+        div {
+            class: "dioxus-bevy-overlay",
+            {children}
+        }
+    }
+}
+
+/// Canvas displaying a paint source and reporting its input
+///
+/// While `locked` and focused, mouse events go to a layer covering the window,
+/// which reports them in the canvas' coordinates. Mouse downs on it don't take
+/// focus, so keyboard input stays with the canvas.
+#[component]
+pub(crate) fn InputCanvas(
+    paint_source_id: u64,
+    cursor: &'static str,
+    locked: bool,
+    oninput: EventHandler<BevyInputEvent>,
+) -> Element {
+    // Mounted canvas, used to take keyboard focus on click
+    let mut canvas_element = use_signal(|| None::<Rc<MountedData>>);
+    let mut focused = use_signal(|| false);
+    let capturing = locked && focused();
+
+    // Canvas' top-left corner in the window, to place motion over the lock layer
+    let origin = use_hook(|| Rc::new(Cell::new(Vec2::ZERO)));
+
+    // Bevy ids of the fingers on the canvas
+    let mut touch_ids = use_hook(|| CopyValue::new(input::TouchIds::default()));
+    // When the latest pointer event came from a finger, `None` after a mouse or pen one
+    let mut last_touch = use_hook(|| CopyValue::new(None::<Instant>));
+    let mut track_pointer = move |evt: &PointerEvent| {
+        last_touch.set((evt.pointer_type() == "touch").then(Instant::now));
+    };
+
+    // Mouse and pen pointers are already reported through the mouse events
+    let mut send_touch = move |evt: &PointerEvent, phase| {
+        track_pointer(evt);
+        if let Some(event) = BevyInputEvent::touch(evt, phase, &mut touch_ids.write()) {
+            oninput.call(event);
+        }
+    };
+    // Browsers follow touches with compatibility mouse events, already reported as touches
+    let send_mouse = move |event: Option<BevyInputEvent>| {
+        let after_touch = last_touch.peek().is_some_and(|at| at.elapsed() < COMPAT_MOUSE_WINDOW);
+        if let Some(event) = event.filter(|_| !after_touch) {
+            oninput.call(event);
+        }
+    };
+
+    rsx! {
+        canvas {
+            "src": paint_source_id,
+            style: "display: block; width: 100%; height: 100%; outline: none; cursor: {cursor};",
+            tabindex: "0",
+            onmounted: move |evt| canvas_element.set(Some(evt.data())),
+            onmousemove: {
+                let origin = origin.clone();
+                move |evt: MouseEvent| {
+                    origin.set(input::canvas_origin(&evt));
+                    send_mouse(Some(BevyInputEvent::cursor_moved(&evt)));
+                }
+            },
+            // A tap focuses the canvas too, through its compatibility mousedown
+            onmousedown: move |evt| {
+                if let Some(element) = canvas_element.peek().clone() {
+                    spawn(async move {
+                        let _ = element.set_focus(true).await;
+                    });
+                }
+                send_mouse(BevyInputEvent::mouse_button(&evt, ButtonState::Pressed));
+            },
+            onmouseup: move |evt| send_mouse(BevyInputEvent::mouse_button(&evt, ButtonState::Released)),
+            onwheel: move |evt| oninput.call(BevyInputEvent::mouse_wheel(&evt)),
+            onmouseenter: move |_| send_mouse(Some(BevyInputEvent::CursorEntered)),
+            // The lock layer covering the canvas takes over, the pointer hasn't left
+            onmouseleave: move |_| {
+                if !capturing {
+                    send_mouse(Some(BevyInputEvent::CursorLeft));
+                }
+            },
+            onkeydown: move |evt| oninput.call(BevyInputEvent::keyboard(&evt, ButtonState::Pressed)),
+            onkeyup: move |evt| oninput.call(BevyInputEvent::keyboard(&evt, ButtonState::Released)),
+            onpointerdown: move |evt| send_touch(&evt, TouchPhase::Started),
+            onpointermove: move |evt| send_touch(&evt, TouchPhase::Moved),
+            onpointerup: move |evt| send_touch(&evt, TouchPhase::Ended),
+            onpointercancel: move |evt| send_touch(&evt, TouchPhase::Canceled),
+            // Fingers lifted outside the canvas don't report an up here, so
+            // leaving ends them; the renderer drops the repeat of either
+            onpointerleave: move |evt| send_touch(&evt, TouchPhase::Canceled),
+            onpointerout: move |evt| send_touch(&evt, TouchPhase::Canceled),
+            onfocus: move |_| {
+                focused.set(true);
+                oninput.call(BevyInputEvent::FocusGained);
+            },
+            onblur: move |_| {
+                focused.set(false);
+                oninput.call(BevyInputEvent::FocusLost);
+            },
+        }
+        if capturing {
+            div {
+                style: LOCK_LAYER_STYLE,
+                onmousemove: {
+                    let origin = origin.clone();
+                    move |evt: MouseEvent| send_mouse(Some(BevyInputEvent::cursor_moved_outside(&evt, origin.get())))
+                },
+                onmousedown: move |evt| {
+                    evt.prevent_default();
+                    send_mouse(BevyInputEvent::mouse_button(&evt, ButtonState::Pressed));
+                },
+                onmouseup: move |evt| send_mouse(BevyInputEvent::mouse_button(&evt, ButtonState::Released)),
+                onwheel: move |evt| oninput.call(BevyInputEvent::mouse_wheel(&evt)),
+                onmouseleave: move |_| send_mouse(Some(BevyInputEvent::CursorLeft)),
+                // Touches on the layer only tell apart the mouse events that follow them
+                onpointerdown: move |evt| track_pointer(&evt),
+                onpointermove: move |evt| track_pointer(&evt),
+                onpointerup: move |evt| track_pointer(&evt),
+            }
+        }
+    }
+}
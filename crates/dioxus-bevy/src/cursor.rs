@@ -0,0 +1,240 @@
+//! Cursor requested by Bevy, shown over the canvas
+//!
+//! Bevy systems set `CursorIcon` and `CursorOptions` on the primary window,
+//! which in the embed is the virtual canvas window. A system reports the
+//! resulting CSS cursor and pointer capture to Dioxus whenever they change, and
+//! the canvases of the instance apply the cursor as their `cursor` style.
+//!
+//! Dioxus Native has no pointer lock and can't move the OS cursor, so capture
+//! is emulated: a locked cursor is hidden and its movement only reports
+//! `MouseMotion`. While the focused canvas holds the lock, a transparent layer
+//! covers the whole Dioxus window and reports the motion past the canvas'
+//! edges. The cursor still stops at the window's edges, where motion ends
+//! until it comes back.
+
+use bevy::app::{App, Last, Plugin};
+use bevy::ecs::entity::Entity;
+use bevy::ecs::query::With;
+use bevy::ecs::system::{Local, Query, Res};
+use bevy::ecs::world::World;
+use bevy::window::{CursorGrabMode, CursorIcon, CursorOptions, SystemCursorIcon};
+use dioxus::prelude::*;
+use dioxus_core::Task;
+use futures_util::StreamExt;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::events::DioxusEvents;
+use crate::window::CanvasWindow;
+use crate::{BevyInstanceId, BevyInstanceManager};
+
+/// CSS `cursor` value for the canvas
+#[derive(Clone, Copy, PartialEq)]
+struct CanvasCursor(&'static str);
+
+/// Pointer capture requested through `CursorOptions::grab_mode`
+///
+/// Sent to Dioxus whenever the grab mode of the virtual window changes, and
+/// once when the app starts. Read it with `use_bevy_event::<PointerCapture>`;
+/// components subscribing later start with the current capture.
+///
+/// While locked, pointer movement over the canvas only produces `MouseMotion`,
+/// not `CursorMoved`. Pressing Escape or moving focus away from the canvas
+/// releases the capture and shows the cursor again, as browsers do.
+///
+/// # Example
+/// ```rust,ignore
+/// let capture = use_bevy_event::<PointerCapture>(instance_id);
+/// let captured = capture().is_some_and(|capture| capture.is_captured());
+///
+/// rsx! {
+///     if !captured {
+///         div { class: "hint", "Click to look around" }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerCapture {
+    /// Grab mode of the virtual window
+    pub grab_mode: CursorGrabMode,
+}
+
+impl PointerCapture {
+    /// Whether the cursor is confined to or locked in the canvas
+    pub fn is_captured(&self) -> bool {
+        self.grab_mode != CursorGrabMode::None
+    }
+}
+
+/// Reports the cursor and pointer capture of the virtual window to Dioxus
+pub(crate) struct CanvasCursorPlugin;
+
+impl Plugin for CanvasCursorPlugin {
+    fn build(&self, app: &mut App) {
+        // In `Last`, so cursors set by picking or UI systems in `PostUpdate` are seen
+        app.init_resource::<DioxusEvents>().add_systems(Last, report_cursor);
+    }
+}
+
+/// Send the canvas cursor and pointer capture, when they changed
+fn report_cursor(
+    windows: Query<(Option<&CursorIcon>, &CursorOptions), With<CanvasWindow>>,
+    events: Res<DioxusEvents>,
+    mut last_cursor: Local<Option<CanvasCursor>>,
+    mut last_capture: Local<Option<PointerCapture>>,
+) {
+    let Ok((icon, options)) = windows.single() else {
+        return;
+    };
+
+    let capture = PointerCapture {
+        grab_mode: options.grab_mode,
+    };
+    if *last_capture != Some(capture) {
+        events.send_state(capture);
+        *last_capture = Some(capture);
+    }
+
+    // A locked pointer is hidden whatever its visibility says
+    let cursor = if !options.visible || options.grab_mode == CursorGrabMode::Locked {
+        CanvasCursor("none")
+    } else {
+        // Custom cursor images have no CSS equivalent here; show the default one
+        let icon = icon.and_then(CursorIcon::as_system).copied().unwrap_or_default();
+        CanvasCursor(css_cursor(icon))
+    };
+    if *last_cursor != Some(cursor) {
+        events.send_state(cursor);
+        *last_cursor = Some(cursor);
+    }
+}
+
+/// Whether the virtual window's cursor is locked in place
+pub(crate) fn is_locked(world: &World, window: Entity) -> bool {
+    world
+        .get::<CursorOptions>(window)
+        .is_some_and(|options| options.grab_mode == CursorGrabMode::Locked)
+}
+
+/// Release a captured pointer and show the cursor again
+pub(crate) fn release_capture(world: &mut World, window: Entity) {
+    let Some(mut options) = world.get_mut::<CursorOptions>(window) else {
+        return;
+    };
+    if options.grab_mode != CursorGrabMode::None {
+        options.grab_mode = CursorGrabMode::None;
+        options.visible = true;
+    }
+}
+
+/// CSS name of a system cursor
+///
+/// `SystemCursorIcon` follows the CSS cursor list, so every icon has one.
+fn css_cursor(icon: SystemCursorIcon) -> &'static str {
+    match icon {
+        SystemCursorIcon::Default => "default",
+        SystemCursorIcon::ContextMenu => "context-menu",
+        SystemCursorIcon::Help => "help",
+        SystemCursorIcon::Pointer => "pointer",
+        SystemCursorIcon::Progress => "progress",
+        SystemCursorIcon::Wait => "wait",
+        SystemCursorIcon::Cell => "cell",
+        SystemCursorIcon::Crosshair => "crosshair",
+        SystemCursorIcon::Text => "text",
+        SystemCursorIcon::VerticalText => "vertical-text",
+        SystemCursorIcon::Alias => "alias",
+        SystemCursorIcon::Copy => "copy",
+        SystemCursorIcon::Move => "move",
+        SystemCursorIcon::NoDrop => "no-drop",
+        SystemCursorIcon::NotAllowed => "not-allowed",
+        SystemCursorIcon::Grab => "grab",
+        SystemCursorIcon::Grabbing => "grabbing",
+        SystemCursorIcon::EResize => "e-resize",
+        SystemCursorIcon::NResize => "n-resize",
+        SystemCursorIcon::NeResize => "ne-resize",
+        SystemCursorIcon::NwResize => "nw-resize",
+        SystemCursorIcon::SResize => "s-resize",
+        SystemCursorIcon::SeResize => "se-resize",
+        SystemCursorIcon::SwResize => "sw-resize",
+        SystemCursorIcon::WResize => "w-resize",
+        SystemCursorIcon::EwResize => "ew-resize",
+        SystemCursorIcon::NsResize => "ns-resize",
+        SystemCursorIcon::NeswResize => "nesw-resize",
+        SystemCursorIcon::NwseResize => "nwse-resize",
+        SystemCursorIcon::ColResize => "col-resize",
+        SystemCursorIcon::RowResize => "row-resize",
+        SystemCursorIcon::AllScroll => "all-scroll",
+        SystemCursorIcon::ZoomIn => "zoom-in",
+        SystemCursorIcon::ZoomOut => "zoom-out",
+    }
+}
+
+/// CSS cursor Bevy currently requests for the canvases of an instance
+pub(crate) fn use_canvas_cursor(
+    manager: Signal<BevyInstanceManager>,
+    instance_id: BevyInstanceId,
+) -> Signal<&'static str> {
+    use_instance_state(manager, instance_id, "default", |CanvasCursor(css)| css)
+}
+
+/// Whether Bevy currently locks the pointer of an instance
+pub(crate) fn use_pointer_locked(manager: Signal<BevyInstanceManager>, instance_id: BevyInstanceId) -> Signal<bool> {
+    use_instance_state(manager, instance_id, false, |capture: PointerCapture| {
+        capture.grab_mode == CursorGrabMode::Locked
+    })
+}
+
+/// Follow a state the app of `instance_id` sends, switching over when the instance changes
+///
+/// Starts from the state the app last sent, or `initial` if it sent none yet.
+fn use_instance_state<T: Clone + Send + 'static, V: 'static>(
+    manager: Signal<BevyInstanceManager>,
+    instance_id: BevyInstanceId,
+    initial: V,
+    map: fn(T) -> V,
+) -> Signal<V> {
+    let value = use_signal(|| initial);
+    let task = use_hook(|| Rc::new(Cell::new(None::<Task>)));
+    use_effect(use_reactive((&instance_id,), move |(instance_id,)| {
+        let mut states = manager.peek().event_bus(&instance_id).subscribe::<T>();
+        let mut value = value;
+        let subscription = spawn(async move {
+            while let Some(state) = states.next().await {
+                value.set(map(state));
+            }
+        });
+        if let Some(previous) = task.replace(Some(subscription)) {
+            previous.cancel();
+        }
+    }));
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_cursors_map_to_css_names() {
+        assert_eq!(css_cursor(SystemCursorIcon::Default), "default");
+        assert_eq!(css_cursor(SystemCursorIcon::Pointer), "pointer");
+        assert_eq!(css_cursor(SystemCursorIcon::NotAllowed), "not-allowed");
+        assert_eq!(css_cursor(SystemCursorIcon::NeswResize), "nesw-resize");
+        assert_eq!(css_cursor(SystemCursorIcon::ZoomOut), "zoom-out");
+        assert_eq!(css_cursor(SystemCursorIcon::default()), "default");
+    }
+
+    #[test]
+    fn css_cursor_names_are_kebab_case_icon_names() {
+        for icon in [
+            SystemCursorIcon::ContextMenu,
+            SystemCursorIcon::VerticalText,
+            SystemCursorIcon::NoDrop,
+            SystemCursorIcon::AllScroll,
+            SystemCursorIcon::NwseResize,
+        ] {
+            let css = css_cursor(icon).replace('-', "");
+            assert!(format!("{icon:?}").eq_ignore_ascii_case(&css), "{icon:?} -> {css}");
+        }
+    }
+}
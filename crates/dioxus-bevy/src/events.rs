@@ -0,0 +1,188 @@
+//! Bevy to Dioxus event delivery
+//!
+//! Each Bevy instance owns an event bus. Bevy systems emit typed events into
+//! it, and Dioxus subscribers receive them over async channels, so the Dioxus
+//! scheduler is woken when an event arrives instead of polling.
+
+use bevy::ecs::resource::Resource;
+use dioxus::prelude::{UnboundedReceiver, UnboundedSender};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Per-instance fan-out of events emitted by a Bevy app to Dioxus subscribers
+#[derive(Clone, Default)]
+pub(crate) struct BevyEventBus {
+    topics: Arc<Mutex<HashMap<TypeId, Topic>>>,
+}
+
+/// Subscribers of one event type
+#[derive(Default)]
+struct Topic {
+    /// `UnboundedSender<T>` of each subscriber
+    senders: Vec<Box<dyn Subscriber>>,
+    /// Last state emitted with `emit_state`, handed to new subscribers
+    state: Option<Box<dyn Any + Send>>,
+}
+
+/// Type-erased sender of one subscriber
+trait Subscriber: Send {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Whether the subscriber dropped its receiver
+    fn is_closed(&self) -> bool;
+}
+
+impl<T: Send + 'static> Subscriber for UnboundedSender<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_closed(&self) -> bool {
+        UnboundedSender::is_closed(self)
+    }
+}
+
+impl BevyEventBus {
+    /// Subscribe to events of type `T`
+    ///
+    /// If `T` is a state, the receiver starts with its current value.
+    pub(crate) fn subscribe<T: Clone + Send + 'static>(&self) -> UnboundedReceiver<T> {
+        let (sender, receiver) = futures_channel::mpsc::unbounded::<T>();
+        let mut topics = self.topics.lock().unwrap();
+        let topic = topics.entry(TypeId::of::<T>()).or_default();
+        if let Some(state) = topic.state.as_ref().and_then(|state| state.downcast_ref::<T>()) {
+            let _ = sender.unbounded_send(state.clone());
+        }
+        topic.senders.push(Box::new(sender));
+        receiver
+    }
+
+    /// Deliver an event to every live subscriber of its type
+    ///
+    /// Subscribers whose receiver has been dropped are removed.
+    pub(crate) fn emit<T: Clone + Send + 'static>(&self, event: T) {
+        let mut topics = self.topics.lock().unwrap();
+        if let Some(topic) = topics.get_mut(&TypeId::of::<T>()) {
+            topic.senders.retain(|sender| {
+                sender
+                    .as_any()
+                    .downcast_ref::<UnboundedSender<T>>()
+                    .is_some_and(|sender| sender.unbounded_send(event.clone()).is_ok())
+            });
+        }
+    }
+
+    /// Deliver a new value of a state, and keep it for later subscribers
+    pub(crate) fn emit_state<T: Clone + Send + 'static>(&self, state: T) {
+        self.topics
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .state = Some(Box::new(state.clone()));
+        self.emit(state);
+    }
+
+    /// Forget the states of a destroyed app and the subscribers that are gone
+    ///
+    /// Returns whether anyone is still subscribed.
+    pub(crate) fn reset(&self) -> bool {
+        let mut topics = self.topics.lock().unwrap();
+        topics.retain(|_, topic| {
+            topic.state = None;
+            topic.senders.retain(|sender| !sender.is_closed());
+            !topic.senders.is_empty()
+        });
+        !topics.is_empty()
+    }
+}
+
+/// Resource through which Bevy systems emit events to Dioxus
+///
+/// Inserted by `BevyAppRenderer`. Events of any `Clone + Send` type can be sent;
+/// Dioxus components receive them with `use_bevy_event::<T>(instance_id)`.
+/// Events sent before the instance is attached to Dioxus (e.g. from `Startup`
+/// systems) are held back and delivered once it is.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(Clone)]
+/// struct GameOver { score: u32 }
+///
+/// fn check_game_over(score: Res<Score>, events: Res<DioxusEvents>) {
+///     if score.is_finished() {
+///         events.send(GameOver { score: score.value });
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct DioxusEvents {
+    inner: Mutex<DioxusEventsInner>,
+}
+
+/// An event sent before the bus was attached
+type PendingEvent = Box<dyn FnOnce(&BevyEventBus) + Send>;
+
+/// Attached bus, or events waiting for one
+#[derive(Default)]
+struct DioxusEventsInner {
+    bus: Option<BevyEventBus>,
+    pending: Vec<PendingEvent>,
+}
+
+impl DioxusEvents {
+    /// Send an event to every Dioxus subscriber of its type
+    pub fn send<T: Clone + Send + 'static>(&self, event: T) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        match &inner.bus {
+            Some(bus) => bus.emit(event),
+            None => inner.pending.push(Box::new(move |bus| bus.emit(event))),
+        }
+    }
+
+    /// Send the current value of a state, which components subscribing later
+    /// also receive
+    pub(crate) fn send_state<T: Clone + Send + 'static>(&self, state: T) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        match &inner.bus {
+            Some(bus) => bus.emit_state(state),
+            None => inner.pending.push(Box::new(move |bus| bus.emit_state(state))),
+        }
+    }
+
+    /// Attach the instance's event bus and flush events sent before it
+    pub(crate) fn attach(&self, bus: BevyEventBus) {
+        let mut inner = self.inner.lock().unwrap();
+        for deliver in inner.pending.drain(..) {
+            deliver(&bus);
+        }
+        inner.bus = Some(bus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{FutureExt, StreamExt};
+
+    #[test]
+    fn late_subscribers_start_with_the_current_state() {
+        let bus = BevyEventBus::default();
+        let mut early = bus.subscribe::<u32>();
+        bus.emit_state(1_u32);
+        bus.emit_state(2_u32);
+        bus.emit(7_u8);
+
+        let mut late = bus.subscribe::<u32>();
+        assert_eq!(early.next().now_or_never().flatten(), Some(1));
+        assert_eq!(early.next().now_or_never().flatten(), Some(2));
+        assert_eq!(late.next().now_or_never().flatten(), Some(2));
+        assert!(late.next().now_or_never().is_none());
+
+        // Plain events aren't kept
+        assert!(bus.subscribe::<u8>().next().now_or_never().is_none());
+    }
+}
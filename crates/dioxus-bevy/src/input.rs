@@ -0,0 +1,849 @@
+//! Canvas input forwarding
+//!
+//! Translates Dioxus events captured on the Bevy canvas into Bevy input
+//! messages, so systems can use `ButtonInput<MouseButton>`, `ButtonInput<KeyCode>`,
+//! `CursorMoved`, `MouseMotion`, `MouseWheel`, `KeyboardInput`, `TouchInput`
+//! and `Touches` exactly as they would in a windowed app.
+//!
+//! Positions and deltas in `CursorMoved`, `TouchInput`, `MouseMotion` and
+//! `Window::cursor_position` are all physical pixels from the canvas' top-left
+//! corner. That is the space of the canvas texture, whose cameras have a scale
+//! factor of 1, so they go straight into `Camera::viewport_to_world`. The
+//! virtual window keeps a scale factor of 1 to match; divide by
+//! `CanvasViewport::scale_factor` to get CSS pixels.
+
+use bevy::ecs::entity::Entity;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::world::World;
+use bevy::input::gestures::{PinchGesture, RotationGesture};
+use bevy::input::keyboard::{Key, KeyCode, KeyboardFocusLost, KeyboardInput, NativeKey, NativeKeyCode};
+use bevy::input::mouse::{MouseButton, MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::input::touch::{ForceTouch, TouchInput, TouchPhase};
+use bevy::input::ButtonState;
+use bevy::math::Vec2;
+use bevy::window::{CursorEntered, CursorLeft, CursorMoved};
+use dioxus::prelude::{
+    Code, InteractionElementOffset, InteractionLocation, Key as DioxusKey, KeyboardData, MouseData, PointerData,
+    PointerInteraction, WheelData,
+};
+use dioxus::html::geometry::WheelDelta;
+use dioxus::html::input_data::MouseButton as DioxusMouseButton;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::cursor;
+
+/// Lines a wheel scrolls per page, for page deltas converted to Bevy's line unit
+///
+/// About a screen of text, in the range desktop platforms scroll by per page.
+const LINES_PER_PAGE: f32 = 20.0;
+
+/// Input event captured on the Bevy canvas
+///
+/// `BevyComponent` sends these to its renderer through `BevyRenderer::handle_message`.
+/// Positions are relative to the top-left corner of the canvas, in logical (CSS)
+/// pixels, as Dioxus reports them; the renderer scales them to the physical
+/// pixels Bevy sees (see the module docs).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BevyInputEvent {
+    /// Cursor moved to a new position over the canvas
+    CursorMoved {
+        /// Canvas-local cursor position, outside the canvas' bounds while the
+        /// pointer is locked and leaves it
+        position: Vec2,
+    },
+    /// Mouse button pressed or released over the canvas
+    MouseButton {
+        /// Button that changed state
+        button: MouseButton,
+        /// New state of the button
+        state: ButtonState,
+    },
+    /// Mouse wheel scrolled over the canvas
+    MouseWheel {
+        /// Unit of the scroll delta
+        unit: MouseScrollUnit,
+        /// Scroll delta, positive y scrolls up as in Bevy
+        delta: Vec2,
+    },
+    /// Cursor entered the canvas
+    CursorEntered,
+    /// Cursor left the canvas
+    CursorLeft,
+    /// Key pressed or released while the canvas has focus
+    Keyboard {
+        /// Physical key code
+        key_code: KeyCode,
+        /// Logical key, taking the keyboard layout into account
+        logical_key: Key,
+        /// New state of the key
+        state: ButtonState,
+        /// Text produced by the key press, if any
+        text: Option<String>,
+        /// Whether this is an auto-repeat of a held key
+        repeat: bool,
+    },
+    /// Canvas gained keyboard focus
+    FocusGained,
+    /// Canvas lost keyboard focus, all held keys are released
+    FocusLost,
+    /// Finger touched, moved on, or lifted from the canvas
+    Touch {
+        /// Identifier of the contact, stable while the finger touches and not
+        /// reused by later contacts
+        id: u64,
+        /// What the finger did
+        phase: TouchPhase,
+        /// Canvas-local finger position
+        position: Vec2,
+        /// Normalized pressure between 0 and 1
+        force: f32,
+    },
+}
+
+impl BevyInputEvent {
+    /// Create a `CursorMoved` event from a Dioxus mouse event
+    pub fn cursor_moved(data: &MouseData) -> Self {
+        Self::CursorMoved {
+            position: element_position(data),
+        }
+    }
+
+    /// Create a `CursorMoved` event from a Dioxus mouse event on another element
+    ///
+    /// `canvas_origin` is the canvas' top-left corner in client coordinates,
+    /// see `canvas_origin`.
+    pub(crate) fn cursor_moved_outside(data: &MouseData, canvas_origin: Vec2) -> Self {
+        Self::CursorMoved {
+            position: client_position(data) - canvas_origin,
+        }
+    }
+
+    /// Create a `MouseButton` event from a Dioxus mouse event
+    ///
+    /// Returns `None` if the event carries no button Bevy knows about.
+    pub fn mouse_button(data: &MouseData, state: ButtonState) -> Option<Self> {
+        let button = match data.trigger_button()? {
+            DioxusMouseButton::Primary => MouseButton::Left,
+            DioxusMouseButton::Secondary => MouseButton::Right,
+            DioxusMouseButton::Auxiliary => MouseButton::Middle,
+            DioxusMouseButton::Fourth => MouseButton::Back,
+            DioxusMouseButton::Fifth => MouseButton::Forward,
+            DioxusMouseButton::Unknown => return None,
+        };
+        Some(Self::MouseButton { button, state })
+    }
+
+    /// Create a `MouseWheel` event from a Dioxus wheel event
+    ///
+    /// DOM wheel deltas point down/right, Bevy's point up/left, so both axes are negated.
+    /// Bevy has no page unit, so each page of a page delta scrolls 20 lines.
+    pub fn mouse_wheel(data: &WheelData) -> Self {
+        let (unit, delta) = match data.delta() {
+            WheelDelta::Pixels(v) => (MouseScrollUnit::Pixel, Vec2::new(v.x as f32, v.y as f32)),
+            WheelDelta::Lines(v) => (MouseScrollUnit::Line, Vec2::new(v.x as f32, v.y as f32)),
+            WheelDelta::Pages(v) => (
+                MouseScrollUnit::Line,
+                Vec2::new(v.x as f32, v.y as f32) * LINES_PER_PAGE,
+            ),
+        };
+        Self::MouseWheel { unit, delta: -delta }
+    }
+
+    /// Create a `Keyboard` event from a Dioxus keyboard event
+    pub fn keyboard(data: &KeyboardData, state: ButtonState) -> Self {
+        let key = data.key();
+        let text = match (&key, state) {
+            (DioxusKey::Character(text), ButtonState::Pressed) => Some(text.clone()),
+            _ => None,
+        };
+        Self::Keyboard {
+            key_code: convert_code(data.code()),
+            logical_key: convert_key(key),
+            state,
+            text,
+            repeat: data.is_auto_repeating(),
+        }
+    }
+}
+
+impl BevyInputEvent {
+    /// Create a `Touch` event from a Dioxus pointer event
+    ///
+    /// Returns `None` for mouse and pen pointers, which arrive as mouse events,
+    /// and for fingers `ids` doesn't know as touching.
+    pub(crate) fn touch(data: &PointerData, phase: TouchPhase, ids: &mut TouchIds) -> Option<Self> {
+        if data.pointer_type() != "touch" {
+            return None;
+        }
+        let point = data.element_coordinates();
+        Some(Self::Touch {
+            id: ids.id(data.pointer_id(), phase)?,
+            phase,
+            position: Vec2::new(point.x as f32, point.y as f32),
+            force: data.pressure(),
+        })
+    }
+}
+
+/// Next Bevy touch id, shared by every canvas
+static NEXT_TOUCH_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Bevy ids of the fingers touching a canvas
+///
+/// DOM pointer ids are `i32`s a browser may hand to a later contact again,
+/// while Bevy, like winit, expects each contact to have an id of its own. A
+/// finger gets a new id when it touches down, kept until it lifts or is canceled.
+/// Ids are unique across canvases, as the canvases of viewports share an app.
+#[derive(Debug, Default)]
+pub(crate) struct TouchIds {
+    ids: HashMap<i32, u64>,
+}
+
+impl TouchIds {
+    /// Bevy id of a pointer's contact, `None` for a finger that isn't down
+    fn id(&mut self, pointer_id: i32, phase: TouchPhase) -> Option<u64> {
+        match phase {
+            TouchPhase::Started => {
+                let id = NEXT_TOUCH_ID.fetch_add(1, Ordering::Relaxed);
+                self.ids.insert(pointer_id, id);
+                Some(id)
+            }
+            TouchPhase::Moved => self.ids.get(&pointer_id).copied(),
+            TouchPhase::Ended | TouchPhase::Canceled => self.ids.remove(&pointer_id),
+        }
+    }
+}
+
+/// Canvas-local position of a Dioxus mouse event
+fn element_position(data: &MouseData) -> Vec2 {
+    let point = data.element_coordinates();
+    Vec2::new(point.x as f32, point.y as f32)
+}
+
+/// Window-local position of a Dioxus mouse event
+fn client_position(data: &MouseData) -> Vec2 {
+    let point = data.client_coordinates();
+    Vec2::new(point.x as f32, point.y as f32)
+}
+
+/// Top-left corner of the canvas in client coordinates, from a mouse event over it
+pub(crate) fn canvas_origin(data: &MouseData) -> Vec2 {
+    client_position(data) - element_position(data)
+}
+
+/// Match identically named variants of a Dioxus and a Bevy key enum
+macro_rules! map_variants {
+    ($value:expr, $from:ident => $to:ident, $fallback:expr, [$($name:ident),* $(,)?]) => {
+        match $value {
+            $($from::$name => $to::$name,)*
+            _ => $fallback,
+        }
+    };
+}
+
+/// Convert a DOM physical key code into a Bevy `KeyCode`
+fn convert_code(code: Code) -> KeyCode {
+    match code {
+        Code::MetaLeft => KeyCode::SuperLeft,
+        Code::MetaRight => KeyCode::SuperRight,
+        Code::Super => KeyCode::Meta,
+        other => map_variants!(other, Code => KeyCode, KeyCode::Unidentified(NativeKeyCode::Unidentified), [
+            Backquote, Backslash, BracketLeft, BracketRight, Comma, Digit0, Digit1, Digit2,
+            Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, Equal, IntlBackslash,
+            IntlRo, IntlYen, KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ,
+            KeyK, KeyL, KeyM, KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW,
+            KeyX, KeyY, KeyZ, Minus, Period, Quote, Semicolon, Slash, AltLeft, AltRight,
+            Backspace, CapsLock, ContextMenu, ControlLeft, ControlRight, Enter, ShiftLeft,
+            ShiftRight, Space, Tab, Convert, KanaMode, Lang1, Lang2, Lang3, Lang4, Lang5,
+            NonConvert, Delete, End, Help, Home, Insert, PageDown, PageUp, ArrowDown,
+            ArrowLeft, ArrowRight, ArrowUp, NumLock, Numpad0, Numpad1, Numpad2, Numpad3,
+            Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9, NumpadAdd,
+            NumpadBackspace, NumpadClear, NumpadClearEntry, NumpadComma, NumpadDecimal,
+            NumpadDivide, NumpadEnter, NumpadEqual, NumpadHash, NumpadMemoryAdd,
+            NumpadMemoryClear, NumpadMemoryRecall, NumpadMemoryStore, NumpadMemorySubtract,
+            NumpadMultiply, NumpadParenLeft, NumpadParenRight, NumpadStar, NumpadSubtract,
+            Escape, Fn, FnLock, PrintScreen, ScrollLock, Pause, BrowserBack,
+            BrowserFavorites, BrowserForward, BrowserHome, BrowserRefresh, BrowserSearch,
+            BrowserStop, Eject, LaunchApp1, LaunchApp2, LaunchMail, MediaPlayPause,
+            MediaSelect, MediaStop, MediaTrackNext, MediaTrackPrevious, Power, Sleep,
+            AudioVolumeDown, AudioVolumeMute, AudioVolumeUp, WakeUp, Hyper, Turbo, Abort,
+            Resume, Suspend, Again, Copy, Cut, Find, Open, Paste, Props, Select, Undo,
+            Hiragana, Katakana, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11,
+            F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, F25, F26, F27,
+            F28, F29, F30, F31, F32, F33, F34, F35,
+        ]),
+    }
+}
+
+/// Convert a DOM logical key into a Bevy `Key`
+fn convert_key(key: DioxusKey) -> Key {
+    match key {
+        DioxusKey::Character(text) => Key::Character(text.as_str().into()),
+        DioxusKey::Dead => Key::Dead(None),
+        other => map_variants!(other, DioxusKey => Key, Key::Unidentified(NativeKey::Unidentified), [
+            Alt, AltGraph, CapsLock, Control, Fn, FnLock, Meta, NumLock, ScrollLock, Shift,
+            Symbol, SymbolLock, Hyper, Super, Enter, Tab, ArrowDown, ArrowLeft, ArrowRight,
+            ArrowUp, End, Home, PageDown, PageUp, Backspace, Clear, Copy, CrSel, Cut,
+            Delete, EraseEof, ExSel, Insert, Paste, Redo, Undo, Accept, Again, Attn, Cancel,
+            ContextMenu, Escape, Execute, Find, Help, Pause, Play, Props, Select, ZoomIn,
+            ZoomOut, BrightnessDown, BrightnessUp, Eject, LogOff, Power, PowerOff,
+            PrintScreen, Hibernate, Standby, WakeUp, AllCandidates, Alphanumeric, CodeInput,
+            Compose, Convert, FinalMode, GroupFirst, GroupLast, GroupNext, GroupPrevious,
+            ModeChange, NextCandidate, NonConvert, PreviousCandidate, Process,
+            SingleCandidate, HangulMode, HanjaMode, JunjaMode, Eisu, Hankaku, Hiragana,
+            HiraganaKatakana, KanaMode, KanjiMode, Katakana, Romaji, Zenkaku,
+            ZenkakuHankaku, Soft1, Soft2, Soft3, Soft4, ChannelDown, ChannelUp, Close,
+            MailForward, MailReply, MailSend, MediaClose, MediaFastForward, MediaPause,
+            MediaPlay, MediaPlayPause, MediaRecord, MediaRewind, MediaStop, MediaTrackNext,
+            MediaTrackPrevious, New, Open, Print, Save, SpellCheck, Key11, Key12,
+            AudioBalanceLeft, AudioBalanceRight, AudioBassBoostDown, AudioBassBoostToggle,
+            AudioBassBoostUp, AudioFaderFront, AudioFaderRear, AudioSurroundModeNext,
+            AudioTrebleDown, AudioTrebleUp, AudioVolumeDown, AudioVolumeUp, AudioVolumeMute,
+            MicrophoneToggle, MicrophoneVolumeDown, MicrophoneVolumeUp,
+            MicrophoneVolumeMute, SpeechCorrectionList, SpeechInputToggle,
+            LaunchApplication1, LaunchApplication2, LaunchCalendar, LaunchContacts,
+            LaunchMail, LaunchMediaPlayer, LaunchMusicPlayer, LaunchPhone,
+            LaunchScreenSaver, LaunchSpreadsheet, LaunchWebBrowser, LaunchWebCam,
+            LaunchWordProcessor, BrowserBack, BrowserFavorites, BrowserForward, BrowserHome,
+            BrowserRefresh, BrowserSearch, BrowserStop, AppSwitch, Call, Camera,
+            CameraFocus, EndCall, GoBack, GoHome, HeadsetHook, LastNumberRedial,
+            Notification, MannerMode, VoiceDial, TV, TV3DMode, TVAntennaCable,
+            TVAudioDescription, TVAudioDescriptionMixDown, TVAudioDescriptionMixUp,
+            TVContentsMenu, TVDataService, TVInput, TVInputComponent1, TVInputComponent2,
+            TVInputComposite1, TVInputComposite2, TVInputHDMI1, TVInputHDMI2, TVInputHDMI3,
+            TVInputHDMI4, TVInputVGA1, TVMediaContext, TVNetwork, TVNumberEntry, TVPower,
+            TVRadioService, TVSatellite, TVSatelliteBS, TVSatelliteCS, TVSatelliteToggle,
+            TVTerrestrialAnalog, TVTerrestrialDigital, TVTimer, AVRInput, AVRPower,
+            ColorF0Red, ColorF1Green, ColorF2Yellow, ColorF3Blue, ColorF4Grey, ColorF5Brown,
+            ClosedCaptionToggle, Dimmer, DisplaySwap, DVR, Exit, FavoriteClear0,
+            FavoriteClear1, FavoriteClear2, FavoriteClear3, FavoriteRecall0,
+            FavoriteRecall1, FavoriteRecall2, FavoriteRecall3, FavoriteStore0,
+            FavoriteStore1, FavoriteStore2, FavoriteStore3, Guide, GuideNextDay,
+            GuidePreviousDay, Info, InstantReplay, Link, ListProgram, LiveContent, Lock,
+            MediaApps, MediaAudioTrack, MediaLast, MediaSkipBackward, MediaSkipForward,
+            MediaStepBackward, MediaStepForward, MediaTopMenu, NavigateIn, NavigateNext,
+            NavigateOut, NavigatePrevious, NextFavoriteChannel, NextUserProfile, OnDemand,
+            Pairing, PinPDown, PinPMove, PinPToggle, PinPUp, PlaySpeedDown, PlaySpeedReset,
+            PlaySpeedUp, RandomToggle, RcLowBattery, RecordSpeedNext, RfBypass,
+            ScanChannelsToggle, ScreenModeNext, Settings, SplitScreenToggle, STBInput,
+            STBPower, Subtitle, Teletext, VideoModeNext, Wink, ZoomToggle, F1, F2, F3, F4,
+            F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20, F21,
+            F22, F23, F24, F25, F26, F27, F28, F29, F30, F31, F32, F33, F34, F35,
+        ]),
+    }
+}
+
+/// Two-finger gestures synthesized from touches on the canvas
+///
+/// Touch screens don't produce Bevy's `PinchGesture` and `RotationGesture`,
+/// which only come from macOS and iOS trackpads and screens. Insert this
+/// resource to have them written while exactly two fingers move on the canvas,
+/// so camera controls written for those gestures also work on touch screens.
+/// Without it only `TouchInput` is written.
+///
+/// # Example
+/// ```rust,ignore
+/// app.insert_resource(TouchGestures { pinch: true, rotate: true });
+/// ```
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TouchGestures {
+    /// Write `PinchGesture` as the fingers move apart or together
+    pub pinch: bool,
+    /// Write `RotationGesture` as the fingers turn around each other
+    pub rotate: bool,
+}
+
+/// Pointer and keyboard state kept by the renderer between forwarded events
+pub(crate) struct CanvasInputState {
+    last_cursor_position: Option<Vec2>,
+    pressed_buttons: Vec<MouseButton>,
+    touches: HashMap<u64, Vec2>,
+    focused: bool,
+    scale_factor: f32,
+}
+
+impl Default for CanvasInputState {
+    fn default() -> Self {
+        Self {
+            last_cursor_position: None,
+            pressed_buttons: Vec::new(),
+            touches: HashMap::new(),
+            focused: false,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+impl CanvasInputState {
+    /// Last known cursor position over the canvas in physical pixels, `None` when outside
+    pub(crate) fn cursor_position(&self) -> Option<Vec2> {
+        self.last_cursor_position
+    }
+
+    /// Scale factor used to convert logical event positions to physical pixels
+    pub(crate) fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Set the scale factor used to convert logical event positions to physical pixels
+    pub(crate) fn set_scale_factor(&mut self, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Whether the canvas currently has keyboard focus
+    pub(crate) fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Write the Bevy messages for a forwarded input event into the world
+    ///
+    /// Event positions are scaled to physical pixels. A locked pointer only
+    /// produces `MouseMotion`; Escape and focus loss release it.
+    pub(crate) fn apply(&mut self, world: &mut World, window: Entity, event: BevyInputEvent) {
+        self.apply_captured_by(world, window, window, event);
+    }
+
+    /// Like `apply`, for a window whose pointer capture is held by `capture_window`
+    ///
+    /// Viewport windows write their own messages, while the lock is requested
+    /// on, and released from, the primary canvas window.
+    pub(crate) fn apply_captured_by(
+        &mut self,
+        world: &mut World,
+        window: Entity,
+        capture_window: Entity,
+        event: BevyInputEvent,
+    ) {
+        match event {
+            BevyInputEvent::CursorMoved { position } => {
+                let position = position * self.scale_factor;
+                let delta = self.last_cursor_position.map(|last| position - last);
+                if let Some(delta) = delta {
+                    world.write_message(MouseMotion { delta });
+                }
+                self.last_cursor_position = Some(position);
+                if cursor::is_locked(world, capture_window) {
+                    return;
+                }
+                world.write_message(CursorMoved {
+                    window,
+                    position,
+                    delta,
+                });
+            }
+            BevyInputEvent::MouseButton { button, state } => {
+                match state {
+                    ButtonState::Pressed => self.pressed_buttons.push(button),
+                    ButtonState::Released => self.pressed_buttons.retain(|b| *b != button),
+                }
+                world.write_message(MouseButtonInput {
+                    button,
+                    state,
+                    window,
+                });
+            }
+            BevyInputEvent::MouseWheel { unit, delta } => {
+                world.write_message(MouseWheel {
+                    unit,
+                    x: delta.x,
+                    y: delta.y,
+                    window,
+                });
+            }
+            BevyInputEvent::CursorEntered => {
+                world.write_message(CursorEntered { window });
+            }
+            BevyInputEvent::CursorLeft => {
+                // The canvas won't see the mouseup once the cursor is outside,
+                // so release held buttons here to avoid them getting stuck
+                for button in self.pressed_buttons.drain(..) {
+                    world.write_message(MouseButtonInput {
+                        button,
+                        state: ButtonState::Released,
+                        window,
+                    });
+                }
+                self.last_cursor_position = None;
+                world.write_message(CursorLeft { window });
+            }
+            BevyInputEvent::Keyboard {
+                key_code,
+                logical_key,
+                state,
+                text,
+                repeat,
+            } => {
+                if key_code == KeyCode::Escape && state == ButtonState::Pressed {
+                    cursor::release_capture(world, capture_window);
+                }
+                world.write_message(KeyboardInput {
+                    key_code,
+                    logical_key,
+                    state,
+                    text: text.map(|text| text.as_str().into()),
+                    repeat,
+                    window,
+                });
+            }
+            BevyInputEvent::FocusGained => {
+                self.focused = true;
+            }
+            BevyInputEvent::FocusLost => {
+                self.focused = false;
+                cursor::release_capture(world, capture_window);
+                // Bevy clears ButtonInput<KeyCode> and ButtonInput<Key> on this message
+                world.write_message(KeyboardFocusLost);
+                // As a window losing focus would, cancel the fingers still down
+                for (id, position) in self.touches.drain() {
+                    world.write_message(TouchInput {
+                        phase: TouchPhase::Canceled,
+                        position,
+                        window,
+                        force: None,
+                        id,
+                    });
+                }
+            }
+            BevyInputEvent::Touch {
+                id,
+                phase,
+                position,
+                force,
+            } => {
+                let position = position * self.scale_factor;
+                let span_before = finger_span(&self.touches);
+                match phase {
+                    TouchPhase::Started | TouchPhase::Moved => {
+                        self.touches.insert(id, position);
+                    }
+                    // A finger leaving the canvas is canceled, so the end of one
+                    // that already left is dropped
+                    TouchPhase::Ended | TouchPhase::Canceled => {
+                        if self.touches.remove(&id).is_none() {
+                            return;
+                        }
+                    }
+                }
+                world.write_message(TouchInput {
+                    phase,
+                    position,
+                    window,
+                    force: Some(ForceTouch::Normalized(force as f64)),
+                    id,
+                });
+                if phase == TouchPhase::Moved {
+                    if let (Some(before), Some(after)) = (span_before, finger_span(&self.touches)) {
+                        write_gestures(world, before, after);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Line from the finger with the lower id to the other, while exactly two touch
+fn finger_span(touches: &HashMap<u64, Vec2>) -> Option<Vec2> {
+    if touches.len() != 2 {
+        return None;
+    }
+    let mut fingers = touches.iter();
+    let (a, b) = (fingers.next()?, fingers.next()?);
+    let (first, second) = if a.0 < b.0 { (a.1, b.1) } else { (b.1, a.1) };
+    Some(*second - *first)
+}
+
+/// Write the enabled two-finger gestures for the span between the fingers
+/// changing from `before` to `after`
+fn write_gestures(world: &mut World, before: Vec2, after: Vec2) {
+    let Some(gestures) = world.get_resource::<TouchGestures>().copied() else {
+        return;
+    };
+    if before.length() <= f32::EPSILON || after.length() <= f32::EPSILON {
+        return;
+    }
+
+    if gestures.pinch {
+        world.write_message(PinchGesture(after.length() / before.length() - 1.0));
+    }
+    if gestures.rotate {
+        // Canvas y points down, so a clockwise turn on screen is a positive angle here
+        world.write_message(RotationGesture(-before.angle_to(after)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::{spawn_canvas_window, sync_canvas_window};
+    use bevy::camera::{Camera, CameraProjection, ComputedCameraValues, PerspectiveProjection, RenderTargetInfo};
+    use bevy::ecs::message::{Message, Messages};
+    use bevy::math::{UVec2, Vec3};
+    use bevy::transform::components::{GlobalTransform, Transform};
+    use bevy::window::{
+        CursorGrabMode, CursorOptions, Window, WindowBackendScaleFactorChanged, WindowFocused, WindowResized,
+    };
+
+    /// World with the messages the canvas input writes, and a window entity
+    fn input_world() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Messages<CursorMoved>>();
+        world.init_resource::<Messages<CursorEntered>>();
+        world.init_resource::<Messages<CursorLeft>>();
+        world.init_resource::<Messages<MouseMotion>>();
+        world.init_resource::<Messages<MouseButtonInput>>();
+        world.init_resource::<Messages<MouseWheel>>();
+        world.init_resource::<Messages<KeyboardInput>>();
+        world.init_resource::<Messages<KeyboardFocusLost>>();
+        world.init_resource::<Messages<TouchInput>>();
+        world.init_resource::<Messages<PinchGesture>>();
+        world.init_resource::<Messages<RotationGesture>>();
+        let window = world.spawn_empty().id();
+        (world, window)
+    }
+
+    fn written<M: Message + Clone>(world: &World) -> Vec<M> {
+        world
+            .resource::<Messages<M>>()
+            .iter_current_update_messages()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn cursor_positions_are_in_the_canvas_texture_space() {
+        let (mut world, _) = input_world();
+        world.init_resource::<Messages<WindowResized>>();
+        world.init_resource::<Messages<WindowBackendScaleFactorChanged>>();
+        world.init_resource::<Messages<WindowFocused>>();
+        let window = spawn_canvas_window(&mut world);
+        let mut input = CanvasInputState::default();
+        input.set_scale_factor(2.0);
+
+        // A 200x100 CSS pixel canvas on a 2x display renders into a 400x200
+        // texture, which its cameras see at a scale factor of 1
+        let size = UVec2::new(400, 200);
+        let camera = Camera {
+            computed: ComputedCameraValues {
+                clip_from_view: PerspectiveProjection {
+                    aspect_ratio: 2.0,
+                    ..Default::default()
+                }
+                .get_clip_from_view(),
+                target_info: Some(RenderTargetInfo {
+                    physical_size: size,
+                    scale_factor: 1.0,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let transform = GlobalTransform::from(Transform::from_xyz(0.0, 2.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y));
+        let target = Vec3::new(1.0, 0.5, -1.0);
+        let pixel = camera.world_to_viewport(&transform, target).unwrap();
+
+        // Dioxus reports the cursor over the target in CSS pixels
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: pixel / 2.0 });
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: pixel / 2.0 + Vec2::X });
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: pixel / 2.0 });
+        sync_canvas_window(&mut world, window, size, 2.0, &input);
+
+        let moves: Vec<_> = written::<CursorMoved>(&world).iter().map(|m| m.position).collect();
+        assert_eq!(moves, [pixel, pixel + Vec2::new(2.0, 0.0), pixel]);
+        let motion: Vec<_> = written::<MouseMotion>(&world).iter().map(|m| m.delta).collect();
+        assert_eq!(motion, [Vec2::new(2.0, 0.0), Vec2::new(-2.0, 0.0)]);
+
+        let window = world.get::<Window>(window).unwrap();
+        assert_eq!(window.size(), size.as_vec2());
+        assert_eq!(window.resolution.base_scale_factor(), 2.0);
+        assert_eq!(window.cursor_position(), Some(pixel));
+
+        // The ray under the window's cursor passes through the target
+        let ray = camera.viewport_to_world(&transform, window.cursor_position().unwrap()).unwrap();
+        let closest = ray.origin + *ray.direction * (target - ray.origin).dot(*ray.direction);
+        assert!(closest.distance(target) < 1e-3, "{closest} is not {target}");
+    }
+
+    #[test]
+    fn leaving_releases_held_buttons() {
+        let (mut world, window) = input_world();
+        let mut input = CanvasInputState::default();
+
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: Vec2::ONE });
+        for button in [MouseButton::Left, MouseButton::Right] {
+            input.apply(
+                &mut world,
+                window,
+                BevyInputEvent::MouseButton { button, state: ButtonState::Pressed },
+            );
+        }
+        input.apply(
+            &mut world,
+            window,
+            BevyInputEvent::MouseButton { button: MouseButton::Right, state: ButtonState::Released },
+        );
+        input.apply(&mut world, window, BevyInputEvent::CursorLeft);
+
+        let buttons: Vec<_> = written::<MouseButtonInput>(&world)
+            .iter()
+            .map(|m| (m.button, m.state))
+            .collect();
+        assert_eq!(
+            buttons,
+            [
+                (MouseButton::Left, ButtonState::Pressed),
+                (MouseButton::Right, ButtonState::Pressed),
+                (MouseButton::Right, ButtonState::Released),
+                (MouseButton::Left, ButtonState::Released),
+            ]
+        );
+        assert_eq!(written::<CursorLeft>(&world).len(), 1);
+        assert_eq!(input.cursor_position(), None);
+    }
+
+    #[test]
+    fn focus_loss_clears_keyboard_state() {
+        let (mut world, window) = input_world();
+        let mut input = CanvasInputState::default();
+
+        input.apply(&mut world, window, BevyInputEvent::FocusGained);
+        assert!(input.focused());
+        input.apply(&mut world, window, BevyInputEvent::FocusLost);
+        assert!(!input.focused());
+        assert_eq!(written::<KeyboardFocusLost>(&world).len(), 1);
+    }
+
+    /// Lock the pointer of the window, as a Bevy system would
+    fn lock(world: &mut World, window: Entity) {
+        world.entity_mut(window).insert(CursorOptions {
+            grab_mode: CursorGrabMode::Locked,
+            visible: false,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn locked_pointer_only_reports_motion() {
+        let (mut world, window) = input_world();
+        let mut input = CanvasInputState::default();
+        lock(&mut world, window);
+
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: Vec2::new(10.0, 10.0) });
+        // Past the canvas' edge, as reported by the lock layer
+        input.apply(&mut world, window, BevyInputEvent::CursorMoved { position: Vec2::new(-5.0, 12.0) });
+
+        assert!(written::<CursorMoved>(&world).is_empty());
+        let motion: Vec<_> = written::<MouseMotion>(&world).iter().map(|m| m.delta).collect();
+        assert_eq!(motion, [Vec2::new(-15.0, 2.0)]);
+    }
+
+    #[test]
+    fn escape_and_focus_loss_release_the_pointer() {
+        for release in [
+            BevyInputEvent::Keyboard {
+                key_code: KeyCode::Escape,
+                logical_key: Key::Escape,
+                state: ButtonState::Pressed,
+                text: None,
+                repeat: false,
+            },
+            BevyInputEvent::FocusLost,
+        ] {
+            let (mut world, window) = input_world();
+            let mut input = CanvasInputState::default();
+            lock(&mut world, window);
+
+            input.apply(&mut world, window, release);
+            let options = world.get::<CursorOptions>(window).unwrap();
+            assert_eq!(options.grab_mode, CursorGrabMode::None);
+            assert!(options.visible);
+        }
+    }
+
+    fn touch(id: u64, phase: TouchPhase, position: Vec2) -> BevyInputEvent {
+        BevyInputEvent::Touch {
+            id,
+            phase,
+            position,
+            force: 1.0,
+        }
+    }
+
+    #[test]
+    fn gestures_follow_the_span_between_fingers() {
+        let (mut world, _) = input_world();
+        write_gestures(&mut world, Vec2::new(10.0, 0.0), Vec2::new(0.0, 20.0));
+        assert!(written::<PinchGesture>(&world).is_empty());
+
+        world.insert_resource(TouchGestures { pinch: true, rotate: true });
+        write_gestures(&mut world, Vec2::new(10.0, 0.0), Vec2::new(0.0, 20.0));
+        // Apart to twice the distance, turned a quarter clockwise on screen
+        assert_eq!(written::<PinchGesture>(&world)[0].0, 1.0);
+        let rotation = written::<RotationGesture>(&world)[0].0;
+        assert!((rotation + std::f32::consts::FRAC_PI_2).abs() < 1e-5, "{rotation}");
+
+        // Fingers on top of each other give no direction to compare
+        write_gestures(&mut world, Vec2::ZERO, Vec2::X);
+        assert_eq!(written::<PinchGesture>(&world).len(), 1);
+    }
+
+    #[test]
+    fn pinching_two_fingers_writes_gestures() {
+        let (mut world, window) = input_world();
+        world.insert_resource(TouchGestures { pinch: true, rotate: false });
+        let mut input = CanvasInputState::default();
+
+        input.apply(&mut world, window, touch(1, TouchPhase::Started, Vec2::new(0.0, 0.0)));
+        input.apply(&mut world, window, touch(2, TouchPhase::Started, Vec2::new(10.0, 0.0)));
+        input.apply(&mut world, window, touch(2, TouchPhase::Moved, Vec2::new(5.0, 0.0)));
+
+        let pinches: Vec<_> = written::<PinchGesture>(&world).iter().map(|pinch| pinch.0).collect();
+        assert_eq!(pinches, [-0.5]);
+        assert!(written::<RotationGesture>(&world).is_empty());
+    }
+
+    #[test]
+    fn fingers_leaving_the_canvas_are_canceled_once() {
+        let (mut world, window) = input_world();
+        world.insert_resource(TouchGestures { pinch: true, rotate: false });
+        let mut input = CanvasInputState::default();
+
+        input.apply(&mut world, window, touch(1, TouchPhase::Started, Vec2::ZERO));
+        input.apply(&mut world, window, touch(2, TouchPhase::Started, Vec2::X));
+        // Finger 2 leaves, then is lifted outside
+        input.apply(&mut world, window, touch(2, TouchPhase::Canceled, Vec2::X));
+        input.apply(&mut world, window, touch(2, TouchPhase::Ended, Vec2::X));
+
+        let phases: Vec<_> = written::<TouchInput>(&world).iter().map(|touch| (touch.id, touch.phase)).collect();
+        assert_eq!(
+            phases,
+            [(1, TouchPhase::Started), (2, TouchPhase::Started), (2, TouchPhase::Canceled)]
+        );
+
+        // A new second finger pinches again
+        input.apply(&mut world, window, touch(3, TouchPhase::Started, Vec2::new(4.0, 0.0)));
+        input.apply(&mut world, window, touch(3, TouchPhase::Moved, Vec2::new(8.0, 0.0)));
+        assert_eq!(written::<PinchGesture>(&world).len(), 1);
+    }
+
+    #[test]
+    fn every_contact_gets_its_own_touch_id() {
+        let mut ids = TouchIds::default();
+
+        // Negative DOM ids stay apart from small positive ones
+        let first = ids.id(-1, TouchPhase::Started).unwrap();
+        let second = ids.id(1, TouchPhase::Started).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(ids.id(-1, TouchPhase::Moved), Some(first));
+        assert_eq!(ids.id(-1, TouchPhase::Ended), Some(first));
+
+        // Gone once lifted, so a late cancel or move is dropped
+        assert_eq!(ids.id(-1, TouchPhase::Canceled), None);
+        assert_eq!(ids.id(-1, TouchPhase::Moved), None);
+
+        // A DOM id handed out again is a new contact
+        let again = ids.id(-1, TouchPhase::Started).unwrap();
+        assert!(again != first && again != second);
+        assert_eq!(ids.id(1, TouchPhase::Canceled), Some(second));
+    }
+}
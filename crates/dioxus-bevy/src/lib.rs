@@ -7,7 +7,10 @@
 //!
 //! ## Features
 //!
-//! - **Lifecycle Management**: Bevy instances survive component unmount/remount cycles
+//! - **Lifecycle Management**: An instance is destroyed when its last component
+//!   unmounts, or kept for a while per the manager's `RetentionPolicy`. Only keyed
+//!   instances are reused by components mounted later; a scope-id instance
+//!   always belongs to the component that created it
 //! - **Lazy Initialization**: Renderers created when WGPU device is available
 //! - **Reference Counting**: Multiple component instances shares one Bevy app
 //! - **Message Passing**: Type-safe communication between Dioxus UI and Bevy
@@ -16,11 +19,12 @@
 //! ## Quick Start
 //!
 //! ```rust,no_run
+//! use bevy::prelude::*;
 //! use dioxus::prelude::*;
-//! use dioxus_bevy::{BevyComponent};
+//! use dioxus_bevy::bevy_component;
 //!
 //! #[component]
-//! fn App() -> Element {
+//! fn Root() -> Element {
 //!     rsx! {
 //!         MyComponent {}
 //!     }
@@ -30,23 +34,197 @@
 //! fn my_component(app: &mut App) {
 //!     app.add_systems(Startup, setup);
 //! }
+//!
+//! fn setup(mut commands: Commands) {
+//!     commands.spawn(Camera3d::default());
+//! }
 //! ```
 
 // Re-export the macro
 pub use dioxus_bevy_macro::bevy_component;
 
+mod anchor;
+mod camera;
+mod canvas;
+mod cursor;
+mod events;
+mod input;
+mod picking;
+mod props;
+mod rebuild;
+mod target;
+mod viewport;
+mod window;
+
+pub use anchor::{AnchorOcclusion, BevyAnchor, BevyAnchorProps};
+pub use camera::{CanvasCamera, CANVAS_TEXTURE_VIEW};
+pub use cursor::PointerCapture;
+pub use events::DioxusEvents;
+pub use input::{BevyInputEvent, TouchGestures};
+pub use picking::{BevyPickEvent, BevyPickKind};
+pub use props::{bind_prop, use_bevy_binding, use_bevy_prop, BevyProp};
+pub use rebuild::{BevyCarryOver, RebuildKey};
+pub use target::{CanvasAlphaMode, CanvasFormat, ResolveTonemap};
+pub use viewport::{ActiveViewport, BevyViewport, BevyViewportProps, ViewportCamera};
+pub use window::{CanvasViewport, CanvasWindow, ViewportWindow};
+
 use dioxus::prelude::*;
-use dioxus_core::{use_hook_with_cleanup, ScopeId};
+use dioxus_core::{spawn_forever, use_hook_with_cleanup, ScopeId, SuperFrom, Task};
 use dioxus_native::{CustomPaintCtx, CustomPaintSource, DeviceHandle, TextureHandle, DioxusNativeWindowRenderer};
+use events::BevyEventBus;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_util::future::{self, Either};
+use futures_util::StreamExt;
 use std::any::Any;
-use std::collections::HashMap;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Unique identifier for a Bevy instance
 ///
-/// Uses Dioxus's ScopeId, which is unique per component instance and
-/// automatically managed by Dioxus's lifecycle system.
-pub type BevyInstanceId = ScopeId;
+/// Either derived from the Dioxus `ScopeId` of the component owning the
+/// instance, or from a key chosen by the application.
+///
+/// Dioxus recycles scope ids once a component unmounts, so a scope-derived
+/// instance is never handed to a different component: a released one is
+/// replaced when its scope id comes back. Keyed instances are identified by
+/// the key alone, which lets an app survive remounts or be shared between
+/// components on purpose. Cloning an id is cheap, clones of a keyed id share
+/// its key.
+///
+/// # Example
+/// ```rust,ignore
+/// rsx! {
+///     // Both views drive the same Bevy app
+///     BevyComponent { instance_id: BevyInstanceId::keyed("scene"), factory: factory.clone() }
+///     BevyComponent { instance_id: BevyInstanceId::keyed("scene"), factory }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BevyInstanceId(InstanceKey);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum InstanceKey {
+    Scope(ScopeId),
+    Keyed(InstanceKeyValue),
+}
+
+/// Key given to `BevyInstanceId::keyed`, with its hash
+///
+/// Keys are compared by value, so distinct keys never share an instance even
+/// if their hashes collide.
+#[derive(Clone)]
+struct InstanceKeyValue {
+    hash: u64,
+    value: Arc<dyn KeyValue>,
+}
+
+impl PartialEq for InstanceKeyValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.value.eq_key((*other.value).as_any())
+    }
+}
+
+impl Eq for InstanceKeyValue {}
+
+impl Hash for InstanceKeyValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+impl std::fmt::Debug for InstanceKeyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.hash)
+    }
+}
+
+/// Object-safe `Eq` for instance keys
+trait KeyValue: Any + Send + Sync {
+    fn eq_key(&self, other: &dyn Any) -> bool;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<K: Eq + Send + Sync + 'static> KeyValue for K {
+    fn eq_key(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<K>() == Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl BevyInstanceId {
+    /// Identify an instance by the scope of the component owning it
+    pub fn scope(scope: ScopeId) -> Self {
+        Self(InstanceKey::Scope(scope))
+    }
+
+    /// Identify an instance by an application-chosen key
+    ///
+    /// Equal keys always refer to the same instance, e.g. `keyed("editor")`
+    /// and `keyed(String::from("editor"))`; keys of different types or values
+    /// never do.
+    pub fn keyed<K: Hash + Eq + Send + Sync + 'static>(key: K) -> Self {
+        // String slices are keyed as the `String` they are equal to
+        if let Some(key) = (&key as &dyn Any).downcast_ref::<&'static str>() {
+            return Self::keyed(key.to_string());
+        }
+
+        let mut hasher = DefaultHasher::new();
+        key.type_id().hash(&mut hasher);
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        Self(InstanceKey::Keyed(InstanceKeyValue {
+            hash,
+            value: Arc::new(key),
+        }))
+    }
+
+    /// Whether the identity comes from an application key rather than a scope
+    pub fn is_keyed(&self) -> bool {
+        matches!(self.0, InstanceKey::Keyed(_))
+    }
+}
+
+impl From<ScopeId> for BevyInstanceId {
+    fn from(scope: ScopeId) -> Self {
+        Self::scope(scope)
+    }
+}
+
+impl From<&str> for BevyInstanceId {
+    fn from(key: &str) -> Self {
+        Self::keyed(key.to_string())
+    }
+}
+
+impl From<String> for BevyInstanceId {
+    fn from(key: String) -> Self {
+        Self::keyed(key)
+    }
+}
+
+/// Lets optional id props such as `instance_key` take a plain key
+#[doc(hidden)]
+pub struct OptionInstanceIdMarker;
+
+impl SuperFrom<&str, OptionInstanceIdMarker> for Option<BevyInstanceId> {
+    fn super_from(key: &str) -> Self {
+        Some(BevyInstanceId::from(key))
+    }
+}
+
+impl SuperFrom<String, OptionInstanceIdMarker> for Option<BevyInstanceId> {
+    fn super_from(key: String) -> Self {
+        Some(BevyInstanceId::from(key))
+    }
+}
 
 /// Trait for Bevy-backed renderers
 ///
@@ -54,7 +232,16 @@ pub type BevyInstanceId = ScopeId;
 /// Note: Only `Send` is required, not `Sync`, since renderers are accessed via `&mut self`.
 pub trait BevyRenderer: Send {
     /// Render to texture
-    fn render(&mut self, ctx: CustomPaintCtx, width: u32, height: u32) -> Option<TextureHandle>;
+    ///
+    /// `width` and `height` are the canvas size in physical pixels, `scale` is the
+    /// ratio of physical to logical pixels of the display it is shown on.
+    fn render(
+        &mut self,
+        ctx: CustomPaintCtx,
+        width: u32,
+        height: u32,
+        scale: f64,
+    ) -> Option<TextureHandle>;
 
     /// Handle messages (input events, state changes, etc.)
     fn handle_message(&mut self, msg: Box<dyn Any + Send>);
@@ -67,6 +254,37 @@ pub trait BevyRenderer: Send {
 
     /// Shutdown (cleanup before destruction)
     fn shutdown(&mut self) {}
+
+    /// Render one camera view into the canvas of a `BevyViewport`
+    ///
+    /// `viewport` was registered through a viewport message beforehand. Renderers
+    /// without multi-view support show nothing in viewports.
+    fn render_viewport(
+        &mut self,
+        _ctx: CustomPaintCtx,
+        _viewport: u64,
+        _width: u32,
+        _height: u32,
+        _scale: f64,
+    ) -> Option<TextureHandle> {
+        None
+    }
+
+    /// Approximate GPU memory held by the renderer, in bytes
+    ///
+    /// Used by `RetentionPolicy::GpuMemoryBudget` to decide which released
+    /// instances to evict.
+    fn gpu_memory_usage(&self) -> u64 {
+        0
+    }
+
+    /// Hand over every texture registered with Dioxus, before the renderer is replaced
+    ///
+    /// Unregistering needs a paint context, so the manager does it on the next
+    /// render of the instance.
+    fn take_registered_textures(&mut self) -> Vec<TextureHandle> {
+        Vec::new()
+    }
 }
 
 /// Paint source wrapper for a managed Bevy instance
@@ -75,30 +293,39 @@ pub trait BevyRenderer: Send {
 /// with the Bevy instance manager. Handles lazy initialization and lifecycle.
 pub(crate) struct ManagedBevyPaintSource {
     instance_id: BevyInstanceId,
+    /// Generation of the instance this paint source was registered for
+    generation: u64,
     manager: Arc<Mutex<BevyInstanceManagerInner>>,
-    factory: Option<Box<dyn FnOnce(&DeviceHandle) -> Box<dyn BevyRenderer> + Send>>,
 }
 
+/// Creates the renderer of an instance on a device
+///
+/// Retained for the instance's lifetime, so the renderer can be rebuilt on a
+/// new device.
+pub type RendererFactory = Arc<dyn Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync>;
+
 impl CustomPaintSource for ManagedBevyPaintSource {
     fn resume(&mut self, device_handle: &DeviceHandle) {
         let mut mgr = self.manager.lock().unwrap();
 
-        if let Some(instance) = mgr.instances.get_mut(&self.instance_id) {
-            if instance.renderer.is_none() {
-                if let Some(factory) = self.factory.take() {
-                    instance.renderer = Some(factory(device_handle));
-                }
-            }
+        if mgr.instance_mut(&self.instance_id, self.generation).is_none() {
+            return;
+        }
+        mgr.use_device(&self.instance_id, device_handle);
 
+        if let Some(instance) = mgr.instance_mut(&self.instance_id, self.generation) {
             if let Some(renderer) = &mut instance.renderer {
                 renderer.resume(device_handle);
             }
         }
+        drop(mgr);
+
+        replay_pending(&self.manager, &self.instance_id);
     }
 
     fn suspend(&mut self) {
         let mut mgr = self.manager.lock().unwrap();
-        if let Some(instance) = mgr.instances.get_mut(&self.instance_id) {
+        if let Some(instance) = mgr.instance_mut(&self.instance_id, self.generation) {
             if let Some(renderer) = &mut instance.renderer {
                 renderer.suspend();
             }
@@ -107,15 +334,17 @@ impl CustomPaintSource for ManagedBevyPaintSource {
 
     fn render(
         &mut self,
-        ctx: CustomPaintCtx<'_>,
+        mut ctx: CustomPaintCtx<'_>,
         width: u32,
         height: u32,
-        _scale: f64,
+        scale: f64,
     ) -> Option<TextureHandle> {
         let mut mgr = self.manager.lock().unwrap();
-        if let Some(instance) = mgr.instances.get_mut(&self.instance_id) {
+        mgr.unregister_evicted_textures(&mut ctx);
+        if let Some(instance) = mgr.instance_mut(&self.instance_id, self.generation) {
+            instance.unregister_replaced_textures(&mut ctx);
             if let Some(renderer) = &mut instance.renderer {
-                renderer.render(ctx, width, height)
+                renderer.render(ctx, width, height, scale)
             } else {
                 None
             }
@@ -133,6 +362,29 @@ pub(crate) struct BevyInstance {
     renderer: Option<Box<dyn BevyRenderer>>,
     paint_source_id: Option<u64>,
     ref_count: usize,
+    /// When the last component using the instance unmounted
+    released_at: Option<Instant>,
+    /// Distinguishes instances created under the same id over time
+    generation: u64,
+    /// Factory building the renderer
+    factory: RendererFactory,
+    /// Resources kept when the renderer is rebuilt
+    carry_over: Option<BevyCarryOver>,
+    /// Device the current renderer was built on
+    device: Option<DeviceHandle>,
+    /// Textures registered by replaced renderers, unregistered on the next render
+    replaced_textures: Vec<TextureHandle>,
+    /// Latest signal update sent under each key, replayed to rebuilt renderers
+    signal_values: HashMap<String, SignalUpdate>,
+}
+
+impl BevyInstance {
+    /// Unregister the textures of renderers replaced since the last render
+    pub(crate) fn unregister_replaced_textures(&mut self, ctx: &mut CustomPaintCtx<'_>) {
+        for texture in self.replaced_textures.drain(..) {
+            ctx.unregister_texture(texture);
+        }
+    }
 }
 
 impl Drop for BevyInstance {
@@ -149,6 +401,298 @@ impl Drop for BevyInstance {
 /// BevyInstanceManager to allow for interior mutability through Arc<Mutex>.
 pub(crate) struct BevyInstanceManagerInner {
     instances: HashMap<BevyInstanceId, BevyInstance>,
+    /// Bevy-to-Dioxus event buses, kept apart from instances so Dioxus
+    /// can subscribe before the instance is created
+    event_buses: HashMap<BevyInstanceId, BevyEventBus>,
+    /// Messages sent to created instances before their renderer exists, in send
+    /// order, with at most `MAX_PENDING_INPUT` input events each. Signal updates
+    /// aren't queued, the instance's latest values are replayed instead
+    pending_messages: HashMap<BevyInstanceId, VecDeque<Box<dyn Any + Send>>>,
+    /// What to do with instances no component uses anymore
+    retention: RetentionPolicy,
+    /// Paint sources of destroyed instances, waiting to be unregistered
+    evicted_paint_sources: Vec<u64>,
+    /// Textures destroyed instances registered with Dioxus, unregistered on
+    /// the next render of any paint source
+    evicted_textures: Vec<TextureHandle>,
+    /// Wakes the manager's eviction task to look at the released instances again
+    eviction_wakeups: UnboundedSender<()>,
+    /// Receiving end of `eviction_wakeups`, until the eviction task takes it
+    eviction_receiver: Option<UnboundedReceiver<()>>,
+    /// Generation given to the next created instance
+    next_generation: u64,
+    /// Mounted viewports of each instance and the camera they show, replayed
+    /// to renderers built after them
+    viewports: HashMap<BevyInstanceId, HashMap<viewport::ViewportId, ViewportCamera>>,
+}
+
+impl BevyInstanceManagerInner {
+    /// Run an instance's factory on `device`
+    ///
+    /// The new renderer is handed the instance's outbound event bus, so Bevy can
+    /// reach Dioxus, its viewports and the latest signal values, as the app
+    /// starts from its setup. Other messages sent while it didn't exist yet are
+    /// left for `replay_pending`, once the manager is unlocked.
+    fn build_renderer(&mut self, instance_id: &BevyInstanceId, device: &DeviceHandle) {
+        let Some(instance) = self.instances.get_mut(instance_id) else {
+            return;
+        };
+
+        let mut renderer = (instance.factory)(device);
+        let events = self.event_buses.entry(instance_id.clone()).or_default().clone();
+        renderer.handle_message(Box::new(events));
+        if let Some(viewports) = self.viewports.get(instance_id) {
+            for (id, camera) in viewports {
+                renderer.handle_message(Box::new(viewport::ViewportMessage::Add {
+                    id: *id,
+                    camera: camera.clone(),
+                }));
+            }
+        }
+        for update in instance.signal_values.values() {
+            renderer.handle_message(Box::new(update.clone()));
+        }
+        instance.renderer = Some(renderer);
+        instance.device = Some(device.clone());
+    }
+
+    /// Make sure an instance's renderer runs on `device`
+    ///
+    /// Builds the renderer on first use. After a device loss or surface
+    /// recreation Dioxus resumes with a new device, and the old renderer's
+    /// `RenderDevice` wraps the dead one, so it is replaced.
+    pub(crate) fn use_device(&mut self, instance_id: &BevyInstanceId, device: &DeviceHandle) {
+        let Some(instance) = self.instances.get(instance_id) else {
+            return;
+        };
+        if instance.renderer.is_none() {
+            self.build_renderer(instance_id, device);
+        } else if instance.device.as_ref().is_some_and(|current| current.device != device.device) {
+            self.replace_renderer(instance_id, device, false);
+        }
+    }
+
+    /// Tear down an instance's renderer and build a new one on `device`
+    ///
+    /// The instance's carry-over resources are copied into the new app, which
+    /// also gets the latest signal updates again. When the old renderer's device
+    /// is gone, `shut_down` should be false so it is dropped without running
+    /// another update on it.
+    fn replace_renderer(&mut self, instance_id: &BevyInstanceId, device: &DeviceHandle, shut_down: bool) {
+        let Some(instance) = self.instances.get_mut(instance_id) else {
+            return;
+        };
+        let Some(mut old_renderer) = instance.renderer.take() else {
+            return;
+        };
+
+        let carried = instance
+            .carry_over
+            .as_ref()
+            .map(|carry_over| carry_over.extract(old_renderer.as_mut()))
+            .unwrap_or_default();
+        if shut_down {
+            old_renderer.shutdown();
+        }
+        instance.replaced_textures.extend(old_renderer.take_registered_textures());
+        drop(old_renderer);
+
+        self.build_renderer(instance_id, device);
+        let Some(instance) = self.instances.get_mut(instance_id) else {
+            return;
+        };
+        if let Some(renderer) = &mut instance.renderer {
+            for update in carried {
+                renderer.handle_message(Box::new(update));
+            }
+        }
+    }
+
+    /// Get an instance if it is still the given generation
+    fn instance_mut(&mut self, instance_id: &BevyInstanceId, generation: u64) -> Option<&mut BevyInstance> {
+        self.instances
+            .get_mut(instance_id)
+            .filter(|instance| instance.generation == generation)
+    }
+
+    /// Remove the released instances the retention policy doesn't keep
+    ///
+    /// They are returned rather than dropped, so their renderers are shut down
+    /// once the manager is unlocked.
+    #[must_use]
+    fn evict_released(&mut self) -> Vec<BevyInstance> {
+        let released: Vec<_> = self
+            .instances
+            .iter()
+            .filter_map(|(id, instance)| {
+                let memory = instance.renderer.as_ref().map_or(0, |r| r.gpu_memory_usage());
+                instance.released_at.map(|at| (id.clone(), at, memory))
+            })
+            .collect();
+
+        self.retention
+            .evicted(released)
+            .iter()
+            .filter_map(|instance_id| self.destroy(instance_id))
+            .collect()
+    }
+
+    /// When the first timed release expires, with `RetentionPolicy::KeepFor`
+    fn next_expiry(&self) -> Option<Instant> {
+        let RetentionPolicy::KeepFor(duration) = self.retention else {
+            return None;
+        };
+        self.instances
+            .values()
+            .filter_map(|instance| instance.released_at)
+            .min()
+            .map(|at| at + duration)
+    }
+
+    /// Have the eviction task recompute when the next timed release expires
+    fn wake_eviction_task(&self) {
+        let _ = self.eviction_wakeups.unbounded_send(());
+    }
+
+    /// Unregister the textures of destroyed instances from Dioxus
+    pub(crate) fn unregister_evicted_textures(&mut self, ctx: &mut CustomPaintCtx<'_>) {
+        for texture in self.evicted_textures.drain(..) {
+            ctx.unregister_texture(texture);
+        }
+    }
+
+    /// Remove an instance and the messages queued for it
+    ///
+    /// The instance is returned so its renderer is shut down once the manager
+    /// is unlocked. The paint source can't be unregistered from here, as this may
+    /// run while the window is painting. The event bus loses the states of the
+    /// old app, and is only kept while components are subscribed to it, as they
+    /// may see the instance created again.
+    #[must_use]
+    fn destroy(&mut self, instance_id: &BevyInstanceId) -> Option<BevyInstance> {
+        self.pending_messages.remove(instance_id);
+        if self.event_buses.get(instance_id).is_some_and(|bus| !bus.reset()) {
+            self.event_buses.remove(instance_id);
+        }
+        let mut instance = self.instances.remove(instance_id)?;
+        self.evicted_paint_sources.extend(instance.paint_source_id);
+        // Dioxus keeps registered textures alive until they are unregistered
+        self.evicted_textures.append(&mut instance.replaced_textures);
+        if let Some(renderer) = &mut instance.renderer {
+            self.evicted_textures.extend(renderer.take_registered_textures());
+        }
+        Some(instance)
+    }
+}
+
+/// Input events queued per instance while its renderer doesn't exist yet
+///
+/// The oldest ones are dropped past this, as an instance that never gets a
+/// device would otherwise queue every pointer move for good.
+const MAX_PENDING_INPUT: usize = 256;
+
+/// Whether a message is canvas input, which is only queued up to `MAX_PENDING_INPUT`
+fn is_input(msg: &(dyn Any + Send)) -> bool {
+    msg.is::<BevyInputEvent>() || msg.is::<viewport::ViewportInput>()
+}
+
+/// Hand the messages queued for an instance to its renderer
+///
+/// The renderer is taken out of the instance while they run, so world updates
+/// in them can use the manager without deadlocking. Messages sent meanwhile
+/// are queued and handled in the next round.
+pub(crate) fn replay_pending(manager: &Mutex<BevyInstanceManagerInner>, instance_id: &BevyInstanceId) {
+    loop {
+        let (mut renderer, pending) = {
+            let mut inner = manager.lock().unwrap();
+            let inner = &mut *inner;
+            let Some(instance) = inner.instances.get_mut(instance_id) else {
+                return;
+            };
+            if instance.renderer.is_none() {
+                return;
+            }
+            let Some(pending) = inner.pending_messages.remove(instance_id) else {
+                return;
+            };
+            (instance.renderer.take().unwrap(), pending)
+        };
+
+        for msg in pending {
+            renderer.handle_message(msg);
+        }
+
+        let mut inner = manager.lock().unwrap();
+        match inner.instances.get_mut(instance_id) {
+            Some(instance) if instance.renderer.is_none() => instance.renderer = Some(renderer),
+            // Destroyed or rebuilt while the messages ran
+            _ => {
+                inner.evicted_textures.extend(renderer.take_registered_textures());
+                renderer.shutdown();
+            }
+        }
+    }
+}
+
+/// What happens to a Bevy instance once no component uses it anymore
+///
+/// By default an instance is destroyed when its last component unmounts.
+/// Keeping released instances around lets a quick unmount/remount (e.g. a panel
+/// swap) reuse the running app instead of rebuilding it; the policy bounds how
+/// many of them stay alive. Evicted ones are shut down, and their paint source
+/// and textures are unregistered.
+///
+/// Set it on the manager provided at the root of the app:
+///
+/// ```rust,ignore
+/// use_context_provider(|| {
+///     Signal::new(BevyInstanceManager::with_retention_policy(RetentionPolicy::KeepLast(2)))
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetentionPolicy {
+    /// Destroy an instance as soon as its last component unmounts
+    #[default]
+    DestroyImmediately,
+    /// Keep released instances for this long
+    KeepFor(Duration),
+    /// Keep at most this many released instances, evicting the least recently used
+    KeepLast(usize),
+    /// Keep released instances while their GPU memory fits in this many bytes,
+    /// evicting the least recently used
+    GpuMemoryBudget(u64),
+    /// Never destroy released instances
+    KeepForever,
+}
+
+impl RetentionPolicy {
+    /// Released instances to evict, from their id, release time and GPU memory
+    fn evicted(&self, mut released: Vec<(BevyInstanceId, Instant, u64)>) -> Vec<BevyInstanceId> {
+        // Most recently released first
+        released.sort_by_key(|released| std::cmp::Reverse(released.1));
+
+        match *self {
+            RetentionPolicy::KeepForever => Vec::new(),
+            RetentionPolicy::DestroyImmediately => released.into_iter().map(|(id, ..)| id).collect(),
+            RetentionPolicy::KeepFor(duration) => released
+                .into_iter()
+                .filter(|(_, at, _)| at.elapsed() >= duration)
+                .map(|(id, ..)| id)
+                .collect(),
+            RetentionPolicy::KeepLast(count) => released.into_iter().skip(count).map(|(id, ..)| id).collect(),
+            RetentionPolicy::GpuMemoryBudget(budget) => {
+                let mut used = 0;
+                released
+                    .into_iter()
+                    .filter(|(_, _, memory)| {
+                        used += memory;
+                        used > budget
+                    })
+                    .map(|(id, ..)| id)
+                    .collect()
+            }
+        }
+    }
 }
 
 /// Global Bevy instance manager
@@ -159,15 +703,106 @@ pub(crate) struct BevyInstanceManagerInner {
 #[derive(Clone)]
 pub struct BevyInstanceManager {
     inner: Arc<Mutex<BevyInstanceManagerInner>>,
+    /// Window renderer the paint sources are registered with
+    window_renderer: Rc<OnceCell<DioxusNativeWindowRenderer>>,
 }
 
 impl BevyInstanceManager {
     /// Create a new Bevy instance manager
     pub fn new() -> Self {
+        Self::with_retention_policy(RetentionPolicy::default())
+    }
+
+    /// Create a new Bevy instance manager with a retention policy for released instances
+    pub fn with_retention_policy(retention: RetentionPolicy) -> Self {
+        let (eviction_wakeups, eviction_receiver) = futures_channel::mpsc::unbounded();
         Self {
             inner: Arc::new(Mutex::new(BevyInstanceManagerInner {
                 instances: HashMap::new(),
+                event_buses: HashMap::new(),
+                pending_messages: HashMap::new(),
+                retention,
+                evicted_paint_sources: Vec::new(),
+                evicted_textures: Vec::new(),
+                eviction_wakeups,
+                eviction_receiver: Some(eviction_receiver),
+                next_generation: 0,
+                viewports: HashMap::new(),
             })),
+            window_renderer: Rc::new(OnceCell::new()),
+        }
+    }
+
+    /// Change the retention policy, applying it to already released instances
+    pub fn set_retention_policy(&self, retention: RetentionPolicy) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.retention = retention;
+            inner.wake_eviction_task();
+        }
+        self.evict_released();
+    }
+
+    /// Destroy a Bevy instance right away, whatever the retention policy
+    ///
+    /// Components subscribed to its events stay subscribed, and get the events
+    /// of an instance created again under the same id.
+    pub fn destroy(&self, instance_id: &BevyInstanceId) {
+        let destroyed = self.inner.lock().unwrap().destroy(instance_id);
+        drop(destroyed);
+        self.unregister_evicted();
+    }
+
+    /// Destroy the released instances the retention policy doesn't keep
+    fn evict_released(&self) {
+        let evicted = self.inner.lock().unwrap().evict_released();
+        drop(evicted);
+        self.unregister_evicted();
+    }
+
+    /// Evict timed releases as they expire
+    ///
+    /// Sleeps until the first release expires, or until woken because the
+    /// released instances or the policy changed.
+    fn spawn_eviction_task(&self) {
+        let Some(mut wakeups) = self.inner.lock().unwrap().eviction_receiver.take() else {
+            return;
+        };
+        // Held weakly: dropping the last manager drops the wakeup sender, which
+        // ends the task
+        let inner = Arc::downgrade(&self.inner);
+        let window_renderer = self.window_renderer.clone();
+        spawn_forever(async move {
+            loop {
+                let Some(expiry) = inner.upgrade().map(|inner| inner.lock().unwrap().next_expiry()) else {
+                    return;
+                };
+                let woken = match expiry {
+                    Some(at) => match future::select(wakeups.next(), async_io::Timer::at(at)).await {
+                        Either::Left((wakeup, _)) => wakeup,
+                        Either::Right(_) => Some(()),
+                    },
+                    None => wakeups.next().await,
+                };
+                let (Some(()), Some(inner)) = (woken, inner.upgrade()) else {
+                    return;
+                };
+                let manager = BevyInstanceManager {
+                    inner,
+                    window_renderer: window_renderer.clone(),
+                };
+                manager.evict_released();
+            }
+        });
+    }
+
+    /// Unregister the paint sources of destroyed instances
+    fn unregister_evicted(&self) {
+        let evicted = std::mem::take(&mut self.inner.lock().unwrap().evicted_paint_sources);
+        if let Some(window_renderer) = self.window_renderer.get() {
+            for paint_source_id in evicted {
+                window_renderer.unregister_custom_paint_source(paint_source_id);
+            }
         }
     }
 
@@ -177,6 +812,7 @@ impl BevyInstanceManager {
     /// If the instance already exists, increments the reference count.
     /// If not, creates a new instance slot and registers paint source.
     /// The actual renderer is created lazily in resume() when device is available.
+    /// The factory is kept to rebuild the renderer if the device changes.
     pub fn get_or_create<F>(
         &self,
         instance_id: BevyInstanceId,
@@ -184,56 +820,178 @@ impl BevyInstanceManager {
         factory: F,
     ) -> u64
     where
-        F: FnOnce(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + 'static,
+        F: Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync + 'static,
     {
-        let mut inner = self.inner.lock().unwrap();
+        self.window_renderer.get_or_init(|| dioxus_renderer.clone());
+        self.spawn_eviction_task();
+        self.unregister_evicted();
+
+        let mut replaced = None;
+        let paint_source_id = {
+            let mut inner = self.inner.lock().unwrap();
+
+            // A released scope-derived instance belongs to a component that is
+            // gone; its scope id being handed out again means a different
+            // component now owns it, which mustn't see anything queued for the old one
+            let stale = inner.instances.get(&instance_id).is_some_and(|instance| {
+                !instance_id.is_keyed() && instance.ref_count == 0
+            });
+            if stale {
+                // Dropped after the manager is unlocked
+                replaced = inner.destroy(&instance_id);
+            }
 
-        if let Some(instance) = inner.instances.get_mut(&instance_id) {
-            instance.ref_count += 1;
-            return instance.paint_source_id.expect("Paint source not registered");
-        }
+            if let Some(instance) = inner.instances.get_mut(&instance_id) {
+                instance.ref_count += 1;
+                instance.released_at = None;
+                return instance.paint_source_id.expect("Paint source not registered");
+            }
 
-        let paint_source = ManagedBevyPaintSource {
-            instance_id,
-            manager: self.inner.clone(),
-            factory: Some(Box::new(factory)),
+            let generation = inner.next_generation;
+            inner.next_generation += 1;
+
+            let paint_source = ManagedBevyPaintSource {
+                instance_id: instance_id.clone(),
+                generation,
+                manager: self.inner.clone(),
+            };
+            let paint_source_id = dioxus_renderer.register_custom_paint_source(Box::new(paint_source));
+
+            let instance = BevyInstance {
+                renderer: None,
+                paint_source_id: Some(paint_source_id),
+                ref_count: 1,
+                released_at: None,
+                generation,
+                factory: Arc::new(factory),
+                carry_over: None,
+                device: None,
+                replaced_textures: Vec::new(),
+                signal_values: HashMap::new(),
+            };
+
+            inner.instances.insert(instance_id, instance);
+            paint_source_id
         };
-        let paint_source_id = dioxus_renderer.register_custom_paint_source(Box::new(paint_source));
+        drop(replaced);
+        self.unregister_evicted();
+        paint_source_id
+    }
 
-        let instance = BevyInstance {
-            renderer: None,
-            paint_source_id: Some(paint_source_id),
-            ref_count: 1,
+    /// Replace the renderer of an instance with one built by a new factory
+    ///
+    /// The current renderer is shut down and the factory runs right away on the
+    /// device it was using; resources listed in `carry_over` are copied from the
+    /// old app into the new one, and again on later device changes. An instance
+    /// without a renderer yet just builds from the new factory when it gets a device.
+    pub fn rebuild<F>(&self, instance_id: &BevyInstanceId, factory: F, carry_over: Option<BevyCarryOver>)
+    where
+        F: Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync + 'static,
+    {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(instance) = inner.instances.get_mut(instance_id) else {
+            return;
         };
+        instance.factory = Arc::new(factory);
+        instance.carry_over = carry_over;
 
-        inner.instances.insert(instance_id, instance);
-        paint_source_id
+        if let Some(device) = instance.device.clone() {
+            inner.replace_renderer(instance_id, &device, true);
+        }
     }
 
-    /// Release a reference to a Bevy instance
+    /// Set the factory an instance's renderer is rebuilt with after a device change
     ///
-    /// Decrements the reference count. If it reaches zero, the instance is destroyed.
-    pub fn release(&self, instance_id: &BevyInstanceId) {
+    /// The running renderer is kept; use `rebuild` to replace it right away.
+    pub fn set_factory<F>(&self, instance_id: &BevyInstanceId, factory: F)
+    where
+        F: Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync + 'static,
+    {
         let mut inner = self.inner.lock().unwrap();
+        if let Some(instance) = inner.instances.get_mut(instance_id) {
+            instance.factory = Arc::new(factory);
+        }
+    }
 
+    /// Set the resources kept when an instance's renderer is rebuilt after a device change
+    pub fn set_carry_over(&self, instance_id: &BevyInstanceId, carry_over: Option<BevyCarryOver>) {
+        let mut inner = self.inner.lock().unwrap();
         if let Some(instance) = inner.instances.get_mut(instance_id) {
-            instance.ref_count -= 1;
+            instance.carry_over = carry_over;
+        }
+    }
+
+    /// Current generation of an instance
+    ///
+    /// Changes every time an instance is created under the id, so holders of
+    /// an old generation can tell their instance was replaced.
+    pub fn generation(&self, instance_id: &BevyInstanceId) -> Option<u64> {
+        let inner = self.inner.lock().unwrap();
+        inner.instances.get(instance_id).map(|instance| instance.generation)
+    }
+
+    /// Release a reference to a Bevy instance
+    ///
+    /// Decrements the reference count. If it reaches zero, the instance is kept
+    /// or destroyed according to the retention policy.
+    pub fn release(&self, instance_id: &BevyInstanceId) {
+        if let Some(generation) = self.generation(instance_id) {
+            self.release_generation(instance_id, generation);
+        }
+    }
+
+    /// Release a reference to a specific generation of a Bevy instance
+    ///
+    /// Does nothing if the instance has been replaced since, so a late release
+    /// from a component of an evicted instance can't affect its successor.
+    pub fn release_generation(&self, instance_id: &BevyInstanceId, generation: u64) {
+        {
+            let mut inner = self.inner.lock().unwrap();
 
-            // DON'T destroy the instance even at ref_count 0
-            // This allows the instance to survive brief unmount/remount cycles during panel swaps
-            // The instance will be reused when the component remounts
+            if let Some(instance) = inner.instance_mut(instance_id, generation) {
+                instance.ref_count = instance.ref_count.saturating_sub(1);
+                if instance.ref_count == 0 {
+                    instance.released_at = Some(Instant::now());
+                }
+            }
+            inner.wake_eviction_task();
         }
+        self.evict_released();
     }
 
     /// Send a message to a Bevy instance
     ///
     /// The message is forwarded to the renderer's handle_message method.
+    /// The renderer is created lazily once a GPU device is available, so
+    /// messages sent to a created instance before that are queued and replayed
+    /// in order right after it is created, keeping only the last
+    /// `MAX_PENDING_INPUT` input events. Messages to ids without an instance are
+    /// dropped. Signal updates aren't queued: the latest `SignalUpdate` of each
+    /// key is kept instead, and sent to every renderer built for the instance.
     pub fn send_message(&self, instance_id: &BevyInstanceId, msg: Box<dyn Any + Send>) {
         let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
 
-        if let Some(instance) = inner.instances.get_mut(instance_id) {
-            if let Some(renderer) = &mut instance.renderer {
-                renderer.handle_message(msg);
+        let Some(instance) = inner.instances.get_mut(instance_id) else {
+            return;
+        };
+        let signal = msg.downcast_ref::<SignalUpdate>();
+        if let Some(update) = signal {
+            instance.signal_values.insert(update.key().to_string(), update.clone());
+        }
+        match &mut instance.renderer {
+            Some(renderer) => renderer.handle_message(msg),
+            // Replayed from `signal_values` once the renderer is built
+            None if signal.is_some() => {}
+            None => {
+                let pending = inner.pending_messages.entry(instance_id.clone()).or_default();
+                let queued_input = pending.iter().filter(|queued| is_input(queued.as_ref())).count();
+                if is_input(msg.as_ref()) && queued_input == MAX_PENDING_INPUT {
+                    if let Some(oldest) = pending.iter().position(|queued| is_input(queued.as_ref())) {
+                        pending.remove(oldest);
+                    }
+                }
+                pending.push_back(msg);
             }
         }
     }
@@ -242,6 +1000,12 @@ impl BevyInstanceManager {
     pub fn send_signal(&self, instance_id: &BevyInstanceId, update: SignalUpdate) {
         self.send_message(instance_id, Box::new(update));
     }
+
+    /// Get the Bevy-to-Dioxus event bus of an instance, creating it if needed
+    pub(crate) fn event_bus(&self, instance_id: &BevyInstanceId) -> BevyEventBus {
+        let mut inner = self.inner.lock().unwrap();
+        inner.event_buses.entry(instance_id.clone()).or_default().clone()
+    }
 }
 
 impl Default for BevyInstanceManager {
@@ -250,6 +1014,24 @@ impl Default for BevyInstanceManager {
     }
 }
 
+/// The app's instance manager
+///
+/// Takes the manager provided by an ancestor, such as one set up with a
+/// retention policy, or provides a default one at the root. Either way every
+/// component and hook of the app shares it, so instances are shared by key.
+pub(crate) fn use_instance_manager() -> Signal<BevyInstanceManager> {
+    // Owned by the root, so the manager outlives the component that created it
+    use_root_context(|| Signal::new_in_scope(BevyInstanceManager::new(), ScopeId::ROOT))
+}
+
+/// Render a keyed body as a list of one
+///
+/// Keys only take effect between siblings of a list, so a body whose key
+/// changed is replaced by a fresh one rather than diffed against the old one.
+pub(crate) fn keyed_body(body: Element) -> Element {
+    rsx! { {std::iter::once(body)} }
+}
+
 // ============================================================================
 // Launch Config Helper
 // ============================================================================
@@ -280,30 +1062,78 @@ pub fn config() -> Vec<Box<dyn std::any::Any>> {
 /// Props for BevyComponent
 #[derive(Props, Clone)]
 pub struct BevyComponentProps {
-    /// Unique ID for this Bevy instance (uses Dioxus ScopeId)
+    /// Identity of the Bevy instance, from the owning scope or a key
+    #[props(into)]
     pub instance_id: BevyInstanceId,
 
     /// Factory function to create the renderer (wrapped in Arc to allow Clone)
-    pub factory: Arc<dyn Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync>,
+    pub factory: RendererFactory,
+
+    /// Rebuild the Bevy app from the current `factory` whenever this changes
+    ///
+    /// The factory itself isn't compared, so swapping it (e.g. for another
+    /// scene) only takes effect through a new `rebuild_on` value.
+    #[props(default, into)]
+    pub rebuild_on: RebuildKey,
+
+    /// Resources kept from the old app when rebuilding, on a `rebuild_on`
+    /// change or after the GPU device changed
+    #[props(default)]
+    pub carry_over: Option<BevyCarryOver>,
+
+    /// Background drawn where Bevy renders nothing, as Bevy's `ClearColor`
+    ///
+    /// Without it the app keeps its own `ClearColor`, transparent unless the
+    /// setup inserts another. Removing the prop restores that color.
+    #[props(default, into)]
+    pub clear_color: Option<Color>,
+
+    /// Called when an entity in the Bevy view is clicked
+    #[props(default)]
+    pub onpick: Option<EventHandler<BevyPickEvent>>,
+
+    /// Called when the pointer starts or stops hovering an entity
+    #[props(default)]
+    pub onhover: Option<EventHandler<BevyPickEvent>>,
 
-    /// Optional children (rendered as overlay on the canvas)
+    /// Elements laid over the canvas, such as HUDs, toolbars or loading spinners
+    ///
+    /// They fill a layer covering the canvas, positioned relative to it.
+    /// Direct children take pointer events, which then don't reach Bevy; the
+    /// rest of the layer lets them through to the canvas. Give full-size
+    /// layout wrappers `pointer-events: none` to keep the canvas reachable.
+    /// Use `BevyAnchor` to pin an element to an entity.
     #[props(default)]
     pub children: Element,
 }
 
 impl PartialEq for BevyComponentProps {
     fn eq(&self, other: &Self) -> bool {
-        // Compare only instance_id, not the factory function
+        // Compare everything except the factory function, which changes only through `rebuild_on`
         self.instance_id == other.instance_id
+            && self.rebuild_on == other.rebuild_on
+            && self.carry_over == other.carry_over
+            && self.clear_color == other.clear_color
+            && self.onpick == other.onpick
+            && self.onhover == other.onhover
+            && self.children == other.children
     }
 }
 
 /// Bevy-backed component with Dioxus-like API
 ///
+/// Renders a `position: relative` wrapper filling its parent (`width` and
+/// `height` of 100%), holding the canvas and the layer of children. Size the
+/// component through its parent, and expect absolutely positioned children
+/// to be placed relative to the canvas.
+///
+/// A new `instance_id` remounts the canvas and the children, releasing the old
+/// instance and acquiring the new one.
+///
 /// # Example
 ///
 /// ```rust,ignore
-/// let instance_id = current_scope_id();
+/// let instance_id = BevyInstanceId::from(current_scope_id());
 /// rsx! {
 ///     BevyComponent {
 ///         instance_id,
@@ -311,70 +1141,338 @@ impl PartialEq for BevyComponentProps {
 ///     }
 /// }
 /// ```
+///
+/// Children are laid over the canvas:
+///
+/// ```rust,ignore
+/// rsx! {
+///     BevyComponent {
+///         instance_id,
+///         factory,
+///         div {
+///             style: "position: absolute; top: 8px; right: 8px;",
+///             button { onclick: move |_| reset_camera(), "Reset view" }
+///         }
+///     }
+/// }
+/// ```
 #[component]
 pub fn BevyComponent(props: BevyComponentProps) -> Element {
-    let manager = match try_use_context::<Signal<BevyInstanceManager>>() {
-        Some(mgr) => mgr,
-        None => use_context_provider(|| Signal::new(BevyInstanceManager::new())),
+    // The body's hooks all follow one instance, so the body is rendered under
+    // the instance id and a new id mounts a fresh one
+    let key = format!("{:?}", props.instance_id);
+    let BevyComponentProps {
+        instance_id,
+        factory,
+        rebuild_on,
+        carry_over,
+        clear_color,
+        onpick,
+        onhover,
+        children,
+    } = props;
+    let body = rsx! {
+        BevyComponentBody {
+            key: "{key}",
+            instance_id,
+            factory,
+            rebuild_on,
+            carry_over,
+            clear_color,
+            onpick,
+            onhover,
+            {children}
+        }
     };
+    keyed_body(body)
+}
+
+/// Body of a `BevyComponent`, for one instance
+#[component]
+fn BevyComponentBody(props: BevyComponentProps) -> Element {
+    let instance_id = props.instance_id.clone();
+    let manager = use_instance_manager();
 
     let renderer = use_context::<DioxusNativeWindowRenderer>();
 
+    // Clear color from the props, applied to every app built for this component
+    let clear_color = use_hook(|| Arc::new(Mutex::new(props.clear_color)));
+
+    // Anchors in the overlay, likewise registered with every app built
+    let anchors = use_hook(anchor::AnchorRegistry::default);
+    anchor::use_anchor_layer(manager, instance_id.clone(), anchors.clone());
+
+    // Cursor icon, visibility and lock requested by Bevy
+    let cursor = cursor::use_canvas_cursor(manager, instance_id.clone());
+    let locked = cursor::use_pointer_locked(manager, instance_id.clone());
+
     let paint_source_id = use_hook_with_cleanup(
         {
-            let instance_id = props.instance_id;
-            let factory = props.factory.clone();
+            let instance_id = instance_id.clone();
+            let factory = with_component_state(props.factory.clone(), clear_color.clone(), anchors.clone());
             let mut mgr = manager;
             move || {
                 let id = mgr.write().get_or_create(
-                    instance_id,
+                    instance_id.clone(),
                     &renderer,
-                    move |dev| factory(dev),
+                    factory,
                 );
-                (instance_id, mgr, id)
+                let generation = mgr.peek().generation(&instance_id);
+                (instance_id, generation, mgr, id)
+            }
+        },
+        move |(instance_id, generation, mut mgr, _id)| {
+            if let Some(generation) = generation {
+                mgr.write().release_generation(&instance_id, generation);
+            }
+        },
+    ).3;
+
+    // Rebuilds after a device change start from the factory of the latest props
+    use_effect(use_reactive((&props,), {
+        let instance_id = instance_id.clone();
+        let (clear_color, anchors) = (clear_color.clone(), anchors.clone());
+        move |(props,)| {
+            let factory = with_component_state(props.factory, clear_color.clone(), anchors.clone());
+            manager.peek().set_factory(&instance_id, factory);
+        }
+    }));
+
+    // Resources to keep when the renderer is rebuilt after a device change
+    use_effect(use_reactive((&props.carry_over,), {
+        let instance_id = instance_id.clone();
+        move |(carry_over,)| manager.peek().set_carry_over(&instance_id, carry_over)
+    }));
+
+    // Rebuild the app when the rebuild key changes from one render to the next,
+    // with the factory and carry-over of the render that changed it
+    let rebuilt_for = use_hook(|| Rc::new(Cell::new(props.rebuild_on)));
+    let rebuild_with = use_hook(|| Rc::new(RefCell::new((props.factory.clone(), props.carry_over.clone()))));
+    *rebuild_with.borrow_mut() = (props.factory.clone(), props.carry_over.clone());
+    use_effect(use_reactive((&props.rebuild_on,), {
+        let instance_id = instance_id.clone();
+        let (clear_color, anchors) = (clear_color.clone(), anchors.clone());
+        move |(rebuild_on,)| {
+            if rebuilt_for.get() == rebuild_on {
+                return;
+            }
+            rebuilt_for.set(rebuild_on);
+            let (factory, carry_over) = rebuild_with.borrow().clone();
+            manager.peek().rebuild(
+                &instance_id,
+                with_component_state(factory, clear_color.clone(), anchors.clone()),
+                carry_over,
+            );
+        }
+    }));
+
+    // Apply the clear color to the running app, which may have been built
+    // before this component mounted
+    use_effect(use_reactive((&props.clear_color,), {
+        let instance_id = instance_id.clone();
+        let clear_color = clear_color.clone();
+        move |(color,)| {
+            // Without a color, an app shared with other components is left alone
+            let previous = std::mem::replace(&mut *clear_color.lock().unwrap(), color);
+            if color.is_none() && previous.is_none() {
+                return;
+            }
+            let update = WorldUpdate(Box::new(move |world: &mut World| override_clear_color(world, color)));
+            manager.peek().send_message(&instance_id, Box::new(update));
+        }
+    }));
+
+    // Deliver picking results from Bevy to the pick/hover handlers, taken from
+    // the latest props so a parent passing new handlers gets the events
+    let pick_handlers = use_hook(|| Rc::new(Cell::new((None, None))));
+    pick_handlers.set((props.onpick, props.onhover));
+    use_hook(|| {
+        let mut picks = manager.peek().event_bus(&instance_id).subscribe::<BevyPickEvent>();
+        spawn(async move {
+            while let Some(pick) = picks.next().await {
+                let (onpick, onhover) = pick_handlers.get();
+                let handler = match pick.kind {
+                    BevyPickKind::Click => onpick,
+                    BevyPickKind::Over | BevyPickKind::Out => onhover,
+                };
+                if let Some(handler) = handler {
+                    handler.call(pick);
+                }
+            }
+        })
+    });
+
+    // Forward pointer and keyboard input over the canvas to the renderer
+    let send_input = move |event: BevyInputEvent| {
+        manager.peek().send_message(&instance_id, Box::new(event));
+    };
+
+    rsx! {
+        div {
+            style: "position: relative; width: 100%; height: 100%;",
+            canvas::InputCanvas {
+                paint_source_id,
+                cursor: cursor(),
+                locked: locked(),
+                oninput: send_input,
+            }
+            canvas::CanvasOverlay {
+                {props.children}
+            }
+        }
+    }
+}
+
+/// Wrap a factory so the apps it builds start with the component's clear color and anchors
+fn with_component_state(
+    factory: RendererFactory,
+    clear_color: Arc<Mutex<Option<Color>>>,
+    anchors: anchor::AnchorRegistry,
+) -> impl Fn(&DeviceHandle) -> Box<dyn BevyRenderer> + Send + Sync + 'static {
+    move |device| {
+        let mut renderer = factory(device);
+        if let Some(color) = *clear_color.lock().unwrap() {
+            renderer.handle_message(Box::new(WorldUpdate(Box::new(move |world: &mut World| {
+                override_clear_color(world, Some(color));
+            }))));
+        }
+        anchor::restore_anchors(renderer.as_mut(), &anchors);
+        renderer
+    }
+}
+
+/// `ClearColor` of the app itself, kept while the `clear_color` prop overrides it
+#[derive(Resource)]
+struct OwnClearColor(Option<ClearColor>);
+
+/// Override the app's `ClearColor` with the prop's color, or restore it when `None`
+fn override_clear_color(world: &mut World, color: Option<Color>) {
+    match color {
+        Some(color) => {
+            if !world.contains_resource::<OwnClearColor>() {
+                let own = world.get_resource::<ClearColor>().cloned();
+                world.insert_resource(OwnClearColor(own));
+            }
+            world.insert_resource(ClearColor(color));
+        }
+        None => match world.remove_resource::<OwnClearColor>() {
+            Some(OwnClearColor(Some(own))) => world.insert_resource(own),
+            Some(OwnClearColor(None)) => {
+                world.remove_resource::<ClearColor>();
+            }
+            None => {}
+        },
+    }
+}
+
+/// Hook to send messages to a Bevy component
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let send_to_editor = use_bevy_message("code-editor");
+///
+/// onclick: move |_| {
+///     send_to_editor.send(Box::new(CodeEditorMessage::SetText("hello".to_string())));
+/// }
+/// ```
+pub fn use_bevy_message(instance_id: BevyInstanceId) -> BevyMessageSender {
+    let manager = use_instance_manager();
+
+    BevyMessageSender {
+        instance_id,
+        manager,
+    }
+}
+
+/// Hook to receive events emitted by a Bevy component
+///
+/// Subscribes to events of type `T` sent from Bevy systems through the
+/// `DioxusEvents` resource. The returned signal holds the most recent event
+/// and updates, re-rendering readers, whenever a new one arrives. Events sent
+/// in the same frame replace each other; use `use_bevy_event_handler` to see
+/// every one of them. A new `instance_id` clears it and switches over to the
+/// events of that instance.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let instance_id = BevyInstanceId::keyed("game");
+/// let game_over = use_bevy_event::<GameOver>(instance_id);
+///
+/// rsx! {
+///     if let Some(GameOver { score }) = game_over() {
+///         p { "Game over! Score: {score}" }
+///     }
+/// }
+/// ```
+pub fn use_bevy_event<T: Clone + Send + 'static>(instance_id: BevyInstanceId) -> ReadSignal<Option<T>> {
+    let manager = use_instance_manager();
+
+    // Follow the events of the current instance, switching over when it changes
+    let mut latest = use_signal(|| None);
+    let events_task = use_hook(|| Rc::new(Cell::new(None::<Task>)));
+    use_effect(use_reactive((&instance_id,), move |(instance_id,)| {
+        // The old instance's last event isn't one of the new instance's
+        if latest.peek().is_some() {
+            latest.set(None);
+        }
+        let mut events = manager.peek().event_bus(&instance_id).subscribe::<T>();
+        let task = spawn(async move {
+            while let Some(event) = events.next().await {
+                latest.set(Some(event));
             }
-        },
-        move |(instance_id, mut mgr, _id)| {
-            mgr.write().release(&instance_id);
-        },
-    ).2;
-
-    rsx! {
-        canvas {
-            "src": paint_source_id,
-            style: "display: block; width: 100%; height: 100%;",
+        });
+        if let Some(previous) = events_task.replace(Some(task)) {
+            previous.cancel();
         }
-    }
+    }));
+
+    latest.into()
 }
 
-/// Hook to send messages to a Bevy component
+/// Hook to handle every event emitted by a Bevy component
+///
+/// Like `use_bevy_event`, but calls `handler` once for each event of type `T`,
+/// in the order they were sent, instead of keeping only the latest one. The
+/// handler is taken from the latest render, so it can capture current state,
+/// and a new `instance_id` switches over to the events of that instance.
 ///
 /// # Example
 ///
 /// ```rust,ignore
-/// let send_to_editor = use_bevy_message("code-editor");
-///
-/// onclick: move |_| {
-///     send_to_editor.send(Box::new(CodeEditorMessage::SetText("hello".to_string())));
-/// }
+/// let mut log = use_signal(Vec::new);
+/// use_bevy_event_handler(instance_id, move |hit: ProjectileHit| {
+///     log.write().push(format!("{} took {} damage", hit.target, hit.damage));
+/// });
 /// ```
-pub fn use_bevy_message(instance_id: BevyInstanceId) -> BevyMessageSender {
-    let manager = match try_use_context::<Signal<BevyInstanceManager>>() {
-        Some(mgr) => mgr,
-        None => use_context_provider(|| Signal::new(BevyInstanceManager::new())),
-    };
-
-    BevyMessageSender {
-        instance_id,
-        manager,
-    }
+pub fn use_bevy_event_handler<T: Clone + Send + 'static>(
+    instance_id: BevyInstanceId,
+    handler: impl FnMut(T) + 'static,
+) {
+    let manager = use_instance_manager();
+
+    // Follow the events of the current instance, switching over when it changes
+    let handler = use_callback(handler);
+    let events_task = use_hook(|| Rc::new(Cell::new(None::<Task>)));
+    use_effect(use_reactive((&instance_id,), move |(instance_id,)| {
+        let mut events = manager.peek().event_bus(&instance_id).subscribe::<T>();
+        let task = spawn(async move {
+            while let Some(event) = events.next().await {
+                handler.call(event);
+            }
+        });
+        if let Some(previous) = events_task.replace(Some(task)) {
+            previous.cancel();
+        }
+    }));
 }
 
 /// Helper for sending messages to a Bevy component
 ///
 /// Created by `use_bevy_message` hook. Provides methods to send arbitrary
 /// messages or typed signal updates to a Bevy renderer.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct BevyMessageSender {
     instance_id: BevyInstanceId,
     manager: Signal<BevyInstanceManager>,
@@ -397,6 +1495,24 @@ impl BevyMessageSender {
         let update = value.into_signal_update(key.to_string());
         self.manager.peek().send_signal(&self.instance_id, update);
     }
+
+    /// Send a signal update of any type to the Bevy component
+    ///
+    /// Sent as `SignalUpdate::Value`; read it back with `SignalUpdate::get`.
+    pub fn send_signal_value<T: Clone + Send + 'static>(&self, key: &str, value: T) {
+        let update = SignalUpdate::Value(key.to_string(), SignalValue::new(value));
+        self.manager.peek().send_signal(&self.instance_id, update);
+    }
+
+    /// Send a closure to run against the Bevy world before its next update
+    ///
+    /// Sent as a `WorldUpdate` message; `BevyAppRenderer` applies it directly.
+    pub fn send_world_update<F>(&self, update: F)
+    where
+        F: FnOnce(&mut World) + Send + 'static,
+    {
+        self.send(Box::new(WorldUpdate(Box::new(update))));
+    }
 }
 
 // ============================================================================
@@ -426,8 +1542,133 @@ pub enum SignalUpdate {
     U32(String, u32),
     /// String signal update: (key, value)
     String(String, String),
+    /// Signal update of any other type: (key, value)
+    ///
+    /// Read it back with `SignalUpdate::get` or `SignalValue::downcast_ref`.
+    Value(String, SignalValue),
+}
+
+impl SignalUpdate {
+    /// Key/name of the updated signal
+    pub fn key(&self) -> &str {
+        match self {
+            SignalUpdate::Bool(key, _)
+            | SignalUpdate::F32(key, _)
+            | SignalUpdate::F64(key, _)
+            | SignalUpdate::I32(key, _)
+            | SignalUpdate::U32(key, _)
+            | SignalUpdate::String(key, _)
+            | SignalUpdate::Value(key, _) => key,
+        }
+    }
+
+    /// New value of the signal, whatever its type
+    pub fn value(&self) -> &dyn Any {
+        match self {
+            SignalUpdate::Bool(_, value) => value,
+            SignalUpdate::F32(_, value) => value,
+            SignalUpdate::F64(_, value) => value,
+            SignalUpdate::I32(_, value) => value,
+            SignalUpdate::U32(_, value) => value,
+            SignalUpdate::String(_, value) => value,
+            SignalUpdate::Value(_, value) => value.as_any(),
+        }
+    }
+
+    /// Read the value back as `T` if this update is for `key`
+    ///
+    /// Works for every variant, so `get::<f32>("speed")` and
+    /// `get::<Vec3>("offset")` are read the same way.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// fn my_system(receiver: Res<SignalReceiver>, mut offset: ResMut<Offset>) {
+    ///     while let Ok(update) = receiver.receiver.try_recv() {
+    ///         if let Some(value) = update.get::<Vec3>("offset") {
+    ///             offset.0 = value;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        if self.key() != key {
+            return None;
+        }
+        self.value().downcast_ref::<T>().cloned()
+    }
+}
+
+/// Type-erased signal value carried by `SignalUpdate::Value`
+///
+/// Holds any `Clone + Send + 'static` value, so props are not limited to the
+/// scalar variants of `SignalUpdate`.
+pub struct SignalValue {
+    value: Box<dyn CloneAny>,
+    type_name: &'static str,
+}
+
+impl SignalValue {
+    /// Wrap a value
+    pub fn new<T: Clone + Send + 'static>(value: T) -> Self {
+        Self {
+            value: Box::new(value),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+
+    /// Borrow the value if it is a `T`
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    /// Name of the wrapped value's type, for diagnostics
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        (*self.value).as_any()
+    }
+}
+
+impl Clone for SignalValue {
+    fn clone(&self) -> Self {
+        Self {
+            value: (*self.value).clone_box(),
+            type_name: self.type_name,
+        }
+    }
+}
+
+impl std::fmt::Debug for SignalValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SignalValue").field(&self.type_name).finish()
+    }
+}
+
+/// Object-safe `Clone + Any` for `SignalValue`
+trait CloneAny: Any + Send {
+    fn clone_box(&self) -> Box<dyn CloneAny>;
+    fn as_any(&self) -> &dyn Any;
 }
 
+impl<T: Clone + Send + 'static> CloneAny for T {
+    fn clone_box(&self) -> Box<dyn CloneAny> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Message carrying a mutation of the Bevy world
+///
+/// `BevyAppRenderer` runs the closure on its world as soon as the message is
+/// handled, i.e. between two updates, so systems see the change (with change
+/// detection) on the next frame.
+pub struct WorldUpdate(pub Box<dyn FnOnce(&mut World) + Send>);
+
 /// Resource that receives signal updates from Dioxus via a channel
 ///
 /// Add this to your Bevy app to receive typed signal updates from Dioxus.
@@ -508,6 +1749,39 @@ pub fn make_signal_update<T: IntoSignalUpdate>(key: String, value: T) -> SignalU
     value.into_signal_update(key)
 }
 
+/// Support code for `#[bevy_component]`, not public API
+#[doc(hidden)]
+pub mod __private {
+    use super::{IntoSignalUpdate, SignalUpdate, SignalValue};
+
+    /// Prop value being turned into a `SignalUpdate`
+    ///
+    /// Method resolution on `(&&SignalUpdateOf(value)).into_update(key)` picks
+    /// the scalar variant when the type has one and falls back to
+    /// `SignalUpdate::Value` otherwise.
+    pub struct SignalUpdateOf<T>(pub T);
+
+    pub trait IntoScalarUpdate {
+        fn into_update(self, key: &str) -> SignalUpdate;
+    }
+
+    impl<T: IntoSignalUpdate + Clone> IntoScalarUpdate for &&SignalUpdateOf<T> {
+        fn into_update(self, key: &str) -> SignalUpdate {
+            self.0.clone().into_signal_update(key.to_string())
+        }
+    }
+
+    pub trait IntoValueUpdate {
+        fn into_update(self, key: &str) -> SignalUpdate;
+    }
+
+    impl<T: Clone + Send + 'static> IntoValueUpdate for &SignalUpdateOf<T> {
+        fn into_update(self, key: &str) -> SignalUpdate {
+            SignalUpdate::Value(key.to_string(), SignalValue::new(self.0.clone()))
+        }
+    }
+}
+
 // ============================================================================
 // Asset Resolution - Integrate Bevy with Dioxus Asset System
 // ============================================================================
@@ -632,6 +1906,17 @@ macro_rules! extract_signals {
             }
         }
     };
+
+    ($receiver:expr, { $($key:literal : $ty:ty => |$val:ident| $action:expr),* $(,)? }) => {
+        while let Ok(update) = $receiver.receiver.try_recv() {
+            $(
+                if let Some($val) = update.get::<$ty>($key) {
+                    $action;
+                    continue;
+                }
+            )*
+        }
+    };
 }
 
 /// Helper trait for creating Bevy resources from signal values
@@ -657,9 +1942,27 @@ pub trait FromSignalUpdate: Sized {
 pub struct BevyAppRenderer {
     app: App,
     wgpu_device: wgpu::Device,
+    wgpu_queue: wgpu::Queue,
     texture_handle: Option<TextureHandle>,
+    /// The render target textures, kept to free their memory on suspend
+    texture: Option<target::TargetTextures>,
+    /// Tonemapping and alpha conversion pass, created on first use
+    resolve: Option<target::CanvasResolve>,
+    /// Textures freed on suspend or viewport removal, unregistered from Dioxus on the next render
+    stale_texture_handles: Vec<TextureHandle>,
+    suspended: bool,
+    /// Whether suspending paused virtual time, so resuming only undoes that
+    time_paused_by_suspend: bool,
     manual_texture_view_handle: Option<bevy::camera::ManualTextureViewHandle>,
     last_texture_size: (u32, u32),
+    window: Entity,
+    input: input::CanvasInputState,
+    pointer: picking::CanvasPointer,
+    /// Render targets of the `BevyViewport`s showing this app
+    viewports: HashMap<viewport::ViewportId, viewport::ViewportTarget>,
+    viewport_handles: viewport::ViewportHandles,
+    /// Views rendered since the last update; `None` is the main canvas
+    rendered_views: std::collections::HashSet<Option<viewport::ViewportId>>,
     pub signal_sender: SignalSender,
 }
 
@@ -704,6 +2007,7 @@ impl BevyAppRenderer {
                     ..default()
                 })
                 .set(WindowPlugin {
+                    // Replaced by a virtual window mirroring the canvas
                     primary_window: None,
                     exit_condition: bevy::window::ExitCondition::DontExit,
                     close_when_requested: false,
@@ -712,12 +2016,29 @@ impl BevyAppRenderer {
                 .disable::<bevy::winit::WinitPlugin>(),
         );
 
-        // Clear color (transparent by default)
+        // Virtual primary window mirroring the canvas, spawned before user setup
+        // so startup systems can already query it
+        let window = window::spawn_canvas_window(app.world_mut());
+
+        // Attach cameras spawned at any point to the canvas, registered before
+        // user setup so none are missed
+        app.add_plugins(camera::CanvasCameraPlugin { window });
+
+        // Clear color (transparent by default, see `CanvasAlphaMode` for compositing)
         app.insert_resource(ClearColor(Color::srgba(0.0, 0.0, 0.0, 0.0)));
 
         // Add manual texture views resource
         app.insert_resource(ManualTextureViews::default());
 
+        // Canvas size and scale factor, filled in on the first render
+        app.insert_resource(CanvasViewport::default());
+
+        // Camera of the viewport under the pointer, if any
+        app.init_resource::<ActiveViewport>();
+
+        // Outbound events to Dioxus, attached to the instance's bus after creation
+        app.init_resource::<DioxusEvents>();
+
         // Create channel for signal updates
         let (sender, receiver) = unbounded();
         app.insert_resource(SignalReceiver { receiver });
@@ -725,6 +2046,16 @@ impl BevyAppRenderer {
         // User setup
         setup(&mut app);
 
+        // Picking against the forwarded pointer, after user setup so a backend
+        // added there isn't added twice
+        app.add_plugins(picking::CanvasPickingPlugin);
+
+        // Screen positions of `BevyAnchor`s, reported back to Dioxus
+        app.add_plugins(anchor::AnchorPlugin);
+
+        // Cursor set on the virtual window, shown over the canvas
+        app.add_plugins(cursor::CanvasCursorPlugin);
+
         // Initialize
         app.finish();
         app.cleanup();
@@ -733,98 +2064,655 @@ impl BevyAppRenderer {
         Self {
             app,
             wgpu_device: device.device.clone(),
+            wgpu_queue: device.queue.clone(),
             texture_handle: None,
+            texture: None,
+            resolve: None,
+            stale_texture_handles: Vec::new(),
+            suspended: false,
+            time_paused_by_suspend: false,
             manual_texture_view_handle: None,
             last_texture_size: (0, 0),
+            window,
+            input: input::CanvasInputState::default(),
+            pointer: picking::CanvasPointer::default(),
+            viewports: HashMap::new(),
+            viewport_handles: viewport::ViewportHandles::default(),
+            rendered_views: std::collections::HashSet::new(),
             signal_sender: SignalSender { sender },
         }
     }
 
-    fn init_texture(&mut self, mut ctx: CustomPaintCtx<'_>, width: u32, height: u32) {
-        use bevy::camera::{Camera, ManualTextureViewHandle, RenderTarget};
-        use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
-        use bevy::render::texture::{ManualTextureView, ManualTextureViews};
-
+    fn init_texture(&mut self, ctx: &mut CustomPaintCtx<'_>, width: u32, height: u32) {
         if width == 0 || height == 0 {
             return;
         }
 
+        let world = self.app.world_mut();
         let current_size = (width, height);
-        if self.texture_handle.is_some() && self.last_texture_size == current_size {
+        let config_changed = self
+            .texture
+            .as_ref()
+            .is_some_and(|texture| texture.config() != target::TargetConfig::of(world));
+        if self.texture_handle.is_some() && self.last_texture_size == current_size && !config_changed {
             return;
         }
 
-        let world = self.app.world_mut();
-
-        let mut camera_query = world.query::<&Camera>();
-        let camera_count = camera_query.iter(world).count();
-        if camera_count == 0 {
+        // Created even without a camera yet; cameras spawned later are attached
+        // as they appear
+        let Some(textures) = target::create_target(
+            &self.wgpu_device,
+            &mut self.resolve,
+            world,
+            CANVAS_TEXTURE_VIEW,
+            width,
+            height,
+        ) else {
             return;
+        };
+        if let Some(old_handle) = self.texture_handle.take() {
+            ctx.unregister_texture(old_handle);
         }
 
-        if let Some(mut manual_texture_views) = world.get_resource_mut::<ManualTextureViews>() {
-            if self.texture_handle.is_some() {
-                ctx.unregister_texture(self.texture_handle.take().unwrap());
-            }
-            if let Some(old_handle) = self.manual_texture_view_handle {
-                manual_texture_views.remove(&old_handle);
-                self.manual_texture_view_handle = None;
-            }
-
-            let format = TextureFormat::Rgba8UnormSrgb;
-            let wgpu_texture = self.wgpu_device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("bevy_texture"),
-                size: Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: TextureDimension::D2,
-                format,
-                usage: TextureUsages::TEXTURE_BINDING
-                    | TextureUsages::RENDER_ATTACHMENT
-                    | TextureUsages::COPY_SRC,
-                view_formats: &[],
-            });
-            let wgpu_texture_view =
-                wgpu_texture.create_view(&wgpu::TextureViewDescriptor::default());
-            let manual_texture_view = ManualTextureView {
-                texture_view: wgpu_texture_view.into(),
-                size: bevy::math::UVec2::new(width, height),
-                format,
-            };
-            let manual_texture_view_handle = ManualTextureViewHandle(0);
-            manual_texture_views.insert(manual_texture_view_handle, manual_texture_view);
+        // Every camera feeding the canvas renders into the same view, composited by order
+        camera::attach_canvas_cameras(world, self.window, CANVAS_TEXTURE_VIEW);
+        world.resource_mut::<camera::CanvasTarget>().view = Some(CANVAS_TEXTURE_VIEW);
+
+        self.last_texture_size = current_size;
+        self.manual_texture_view_handle = Some(CANVAS_TEXTURE_VIEW);
+        self.texture_handle = Some(ctx.register_texture(textures.output.clone()));
+        self.texture = Some(textures);
+    }
 
-            if let Ok(mut camera) = world.query::<&mut Camera>().single_mut(world) {
-                camera.target = RenderTarget::TextureView(manual_texture_view_handle);
+    /// Unregister textures freed since the last render
+    fn unregister_stale_textures(&mut self, ctx: &mut CustomPaintCtx<'_>) {
+        for stale in self.stale_texture_handles.drain(..) {
+            ctx.unregister_texture(stale);
+        }
+    }
 
-                self.last_texture_size = current_size;
-                self.manual_texture_view_handle = Some(manual_texture_view_handle);
-                self.texture_handle = Some(ctx.register_texture(wgpu_texture));
+    /// Advance the app once per frame, however many views it renders into
+    ///
+    /// A view rendering again means a new frame has started, so the first view
+    /// of each frame runs the update and draws every camera.
+    fn update_for_view(&mut self, view: Option<viewport::ViewportId>) {
+        if self.rendered_views.is_empty() || self.rendered_views.contains(&view) {
+            self.rendered_views.clear();
+            self.app.update();
+            if let Some(resolve) = &self.resolve {
+                let targets = self
+                    .texture
+                    .iter()
+                    .chain(self.viewports.values().filter_map(|target| target.texture.as_ref()));
+                resolve.run(&self.wgpu_device, &self.wgpu_queue, targets);
             }
         }
+        self.rendered_views.insert(view);
+    }
+
+    /// Stop rendering into a viewport and free its texture
+    fn remove_viewport(&mut self, id: viewport::ViewportId) {
+        use bevy::render::texture::ManualTextureViews;
+
+        let Some(mut target) = self.viewports.remove(&id) else {
+            return;
+        };
+        self.rendered_views.remove(&Some(id));
+        let world = self.app.world_mut();
+        viewport::release_camera(world, &target);
+        target.pointer.despawn(world);
+        world.despawn(target.window);
+        if let Some(mut manual_texture_views) = world.get_resource_mut::<ManualTextureViews>() {
+            manual_texture_views.remove(&target.handle);
+        }
+        self.viewport_handles.release(target.handle);
+        if let Some(texture) = target.texture.take() {
+            texture.destroy();
+        }
+        self.stale_texture_handles.extend(target.texture_handle.take());
     }
 }
 
 impl BevyRenderer for BevyAppRenderer {
-    fn render(&mut self, ctx: CustomPaintCtx, width: u32, height: u32) -> Option<TextureHandle> {
-        self.init_texture(ctx, width, height);
-        self.app.update();
+    fn render(
+        &mut self,
+        mut ctx: CustomPaintCtx,
+        width: u32,
+        height: u32,
+        scale: f64,
+    ) -> Option<TextureHandle> {
+        self.unregister_stale_textures(&mut ctx);
+        // Dioxus hands us the canvas size in physical pixels, so the texture is
+        // allocated at full device resolution
+        self.init_texture(&mut ctx, width, height);
+        self.input.set_scale_factor(scale as f32);
+        window::sync_canvas_window(
+            self.app.world_mut(),
+            self.window,
+            UVec2::new(width, height),
+            scale as f32,
+            &self.input,
+        );
+        self.update_for_view(None);
         self.texture_handle.clone()
     }
 
+    fn render_viewport(
+        &mut self,
+        mut ctx: CustomPaintCtx,
+        viewport: u64,
+        width: u32,
+        height: u32,
+        scale: f64,
+    ) -> Option<TextureHandle> {
+        self.unregister_stale_textures(&mut ctx);
+
+        let world = self.app.world_mut();
+        let view = self.viewports.get_mut(&viewport)?;
+        viewport::sync_viewport_window(world, view, UVec2::new(width, height), scale as f32);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let config_changed = view
+            .texture
+            .as_ref()
+            .is_some_and(|texture| texture.config() != target::TargetConfig::of(world));
+        if view.texture_handle.is_none() || view.size != (width, height) || config_changed {
+            let textures = target::create_target(
+                &self.wgpu_device,
+                &mut self.resolve,
+                world,
+                view.handle,
+                width,
+                height,
+            )?;
+            if let Some(old_handle) = view.texture_handle.take() {
+                ctx.unregister_texture(old_handle);
+            }
+            view.size = (width, height);
+            view.texture_handle = Some(ctx.register_texture(textures.output.clone()));
+            view.texture = Some(textures);
+        }
+
+        // Looked up every frame so cameras spawned or renamed later are picked up
+        viewport::attach_camera(world, view);
+
+        let texture_handle = view.texture_handle.clone();
+        self.update_for_view(Some(viewport));
+        texture_handle
+    }
+
     fn handle_message(&mut self, msg: Box<dyn Any + Send>) {
+        // World updates are run by value, so they are taken out first
+        let msg = match msg.downcast::<WorldUpdate>() {
+            Ok(update) => return (update.0)(self.app.world_mut()),
+            Err(msg) => msg,
+        };
+
         // Try to downcast to SignalUpdate and forward to channel
         if let Some(update) = msg.downcast_ref::<SignalUpdate>() {
             let _ = self.signal_sender.sender.send(update.clone());
+        } else if let Some(bus) = msg.downcast_ref::<BevyEventBus>() {
+            self.app.world().resource::<DioxusEvents>().attach(bus.clone());
+        } else if let Some(message) = msg.downcast_ref::<viewport::ViewportMessage>() {
+            match message {
+                viewport::ViewportMessage::Add { id, camera } => {
+                    let world = self.app.world_mut();
+                    let handles = &mut self.viewport_handles;
+                    let target = self.viewports.entry(*id).or_insert_with(|| {
+                        let handle = handles.allocate(world);
+                        viewport::ViewportTarget::new(world, *id, handle, camera.clone())
+                    });
+                    if target.camera != *camera {
+                        viewport::release_camera(world, target);
+                        target.camera = camera.clone();
+                    }
+                }
+                viewport::ViewportMessage::Remove { id } => self.remove_viewport(*id),
+            }
+        } else if msg.is::<viewport::ViewportInput>() {
+            if let Ok(input) = msg.downcast::<viewport::ViewportInput>() {
+                let world = self.app.world_mut();
+                if let Some(target) = self.viewports.get_mut(&input.viewport) {
+                    viewport::apply_input(world, target, self.window, input.event);
+                }
+            }
+        } else if let Ok(event) = msg.downcast::<BevyInputEvent>() {
+            let world = self.app.world_mut();
+            if matches!(*event, BevyInputEvent::CursorEntered) {
+                world.resource_mut::<ActiveViewport>().set_if_neq(ActiveViewport::default());
+            }
+            if let Some(handle) = self.manual_texture_view_handle {
+                let target = bevy::camera::NormalizedRenderTarget::TextureView(handle);
+                self.pointer.apply(world, target, self.input.scale_factor(), &event);
+            }
+            self.input.apply(world, self.window, *event);
+        }
+    }
+
+    fn suspend(&mut self) {
+        use bevy::render::texture::ManualTextureViews;
+        use bevy::time::Virtual;
+
+        if self.suspended {
+            return;
+        }
+        self.suspended = true;
+
+        let world = self.app.world_mut();
+
+        // Freeze animations where they are, unless the app paused time itself
+        let mut time = world.resource_mut::<Time<Virtual>>();
+        if !time.is_paused() {
+            time.pause();
+            self.time_paused_by_suspend = true;
+        }
+
+        // Free the render target; it's recreated on the first render after resuming
+        world.resource_mut::<camera::CanvasTarget>().view = None;
+        if let Some(handle) = self.manual_texture_view_handle.take() {
+            if let Some(mut manual_texture_views) = world.get_resource_mut::<ManualTextureViews>() {
+                manual_texture_views.remove(&handle);
+            }
+        }
+        if let Some(texture) = self.texture.take() {
+            texture.destroy();
+        }
+        // Unregistering needs a paint context, so it waits for the next render
+        self.stale_texture_handles.extend(self.texture_handle.take());
+        self.last_texture_size = (0, 0);
+
+        // Viewports free theirs too, recreated when they render again
+        for target in self.viewports.values_mut() {
+            if let Some(mut manual_texture_views) = world.get_resource_mut::<ManualTextureViews>() {
+                manual_texture_views.remove(&target.handle);
+            }
+            if let Some(texture) = target.texture.take() {
+                texture.destroy();
+            }
+            self.stale_texture_handles.extend(target.texture_handle.take());
         }
     }
 
+    fn resume(&mut self, _device: &DeviceHandle) {
+        use bevy::time::{Real, Virtual};
+
+        if !self.suspended {
+            return;
+        }
+        self.suspended = false;
+
+        let world = self.app.world_mut();
+
+        // Account for the suspended time in the real clock now, so the next
+        // update doesn't see it as one huge frame
+        world.resource_mut::<Time<Real>>().update();
+        if std::mem::take(&mut self.time_paused_by_suspend) {
+            world.resource_mut::<Time<Virtual>>().unpause();
+        }
+    }
+
+    fn take_registered_textures(&mut self) -> Vec<TextureHandle> {
+        let mut textures = std::mem::take(&mut self.stale_texture_handles);
+        textures.extend(self.texture_handle.take());
+        textures.extend(self.viewports.values_mut().filter_map(|target| target.texture_handle.take()));
+        textures
+    }
+
+    fn gpu_memory_usage(&self) -> u64 {
+        // The render targets dominate; Bevy's own buffers aren't tracked
+        self.texture
+            .iter()
+            .chain(self.viewports.values().filter_map(|target| target.texture.as_ref()))
+            .map(|texture| texture.byte_size())
+            .sum()
+    }
+
     fn shutdown(&mut self) {
         self.app.world_mut().write_message(bevy::app::AppExit::Success);
         self.app.update();
+
+        // Free the render targets now rather than when Dioxus lets go of them
+        if let Some(texture) = self.texture.take() {
+            texture.destroy();
+        }
+        for target in self.viewports.values_mut() {
+            if let Some(texture) = target.texture.take() {
+                texture.destroy();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::window::CursorGrabMode;
+    use dioxus::dioxus_core::NoOpMutations;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn signal_updates_read_back_by_key_and_type() {
+        let update = SignalUpdate::F32("speed".to_string(), 2.5);
+        assert_eq!(update.get::<f32>("speed"), Some(2.5));
+        assert_eq!(update.get::<f32>("offset"), None);
+        assert_eq!(update.get::<f64>("speed"), None);
+
+        let update = SignalUpdate::Value("offset".to_string(), SignalValue::new(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(update.get::<Vec3>("offset"), Some(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(update.get::<Vec2>("offset"), None);
+        assert_eq!(update.clone().get::<Vec3>("offset"), Some(Vec3::new(1.0, 2.0, 3.0)));
+    }
+
+    #[test]
+    // `&&` is how the macro calls `into_update`, so the scalar impl wins
+    #[allow(clippy::needless_borrow)]
+    fn prop_values_keep_their_scalar_variant() {
+        use __private::{IntoScalarUpdate, IntoValueUpdate, SignalUpdateOf};
+
+        let update = (&&SignalUpdateOf(3_i32)).into_update("count");
+        assert!(matches!(update, SignalUpdate::I32(ref key, 3) if key == "count"));
+
+        let update = (&&SignalUpdateOf(Vec3::ONE)).into_update("offset");
+        assert!(matches!(update, SignalUpdate::Value(..)));
+        assert_eq!(update.get::<Vec3>("offset"), Some(Vec3::ONE));
+    }
+
+    #[test]
+    fn removing_the_clear_color_prop_restores_the_apps_color() {
+        let own = Color::srgb(0.2, 0.3, 0.4);
+        let mut world = World::new();
+        world.insert_resource(ClearColor(own));
+
+        override_clear_color(&mut world, Some(Color::WHITE));
+        override_clear_color(&mut world, Some(Color::BLACK));
+        assert_eq!(world.resource::<ClearColor>().0, Color::BLACK);
+
+        override_clear_color(&mut world, None);
+        assert_eq!(world.resource::<ClearColor>().0, own);
+        assert!(!world.contains_resource::<OwnClearColor>());
+    }
+
+    #[test]
+    fn retention_policies_evict_released_instances() {
+        let now = Instant::now();
+        let ago = |secs| now.checked_sub(Duration::from_secs(secs)).unwrap();
+        let (a, b, c) = (
+            BevyInstanceId::keyed("a"),
+            BevyInstanceId::keyed("b"),
+            BevyInstanceId::keyed("c"),
+        );
+        // `a` was released last and `c` first
+        let released = vec![(b.clone(), ago(10), 300), (a.clone(), ago(1), 200), (c.clone(), ago(20), 100)];
+        let evicted = |policy: RetentionPolicy| policy.evicted(released.clone());
+        let ids = |ids: &[&BevyInstanceId]| ids.iter().map(|id| (*id).clone()).collect::<Vec<_>>();
+
+        assert_eq!(RetentionPolicy::default(), RetentionPolicy::DestroyImmediately);
+        assert_eq!(evicted(RetentionPolicy::DestroyImmediately), ids(&[&a, &b, &c]));
+        assert_eq!(evicted(RetentionPolicy::KeepForever), ids(&[]));
+        assert_eq!(evicted(RetentionPolicy::KeepFor(Duration::from_secs(5))), ids(&[&b, &c]));
+        assert_eq!(evicted(RetentionPolicy::KeepLast(1)), ids(&[&b, &c]));
+        assert_eq!(evicted(RetentionPolicy::KeepLast(3)), ids(&[]));
+        // The budget is filled most recently released first
+        assert_eq!(evicted(RetentionPolicy::GpuMemoryBudget(500)), ids(&[&c]));
+        assert_eq!(evicted(RetentionPolicy::GpuMemoryBudget(100)), ids(&[&a, &b, &c]));
+    }
+
+    #[test]
+    fn keyed_ids_compare_keys_by_value() {
+        assert_eq!(BevyInstanceId::keyed("editor"), BevyInstanceId::keyed(String::from("editor")));
+        assert_eq!(BevyInstanceId::from("editor"), BevyInstanceId::keyed("editor"));
+        assert_ne!(BevyInstanceId::keyed("editor"), BevyInstanceId::keyed("preview"));
+        // Same value, different key types
+        assert_ne!(BevyInstanceId::keyed(1_u32), BevyInstanceId::keyed(1_u64));
+        assert!(BevyInstanceId::keyed(("level", 2)).is_keyed());
+    }
+
+    /// A factory for apps that are never painted, so never built
+    pub(crate) fn unbuilt_factory() -> RendererFactory {
+        Arc::new(|_: &DeviceHandle| -> Box<dyn BevyRenderer> { unreachable!("nothing is painted in tests") })
+    }
+
+    /// Render `app` once, with the window renderer a native app provides
+    pub(crate) fn render(app: fn() -> Element) -> VirtualDom {
+        let mut dom = VirtualDom::new(app).with_root_context(DioxusNativeWindowRenderer::new());
+        dom.rebuild_in_place();
+        dom
+    }
+
+    /// Run the tasks and effects `dom` has pending, then rerender it
+    pub(crate) fn run_tasks(dom: &mut VirtualDom) {
+        let tasks = Box::pin(dom.wait_for_work());
+        async_io::block_on(future::select(tasks, async_io::Timer::after(Duration::from_millis(20))));
+        dom.render_immediate(&mut NoOpMutations);
+    }
+
+    /// The manager shared by everything in `dom`
+    pub(crate) fn root_manager(dom: &VirtualDom) -> BevyInstanceManager {
+        dom.in_scope(ScopeId::ROOT, || consume_context::<Signal<BevyInstanceManager>>().peek().clone())
+    }
+
+    #[test]
+    fn sibling_components_share_keyed_instances() {
+        fn app() -> Element {
+            rsx! {
+                div {
+                    BevyComponent { instance_id: "shared", factory: unbuilt_factory() }
+                }
+                div {
+                    BevyComponent { instance_id: "shared", factory: unbuilt_factory() }
+                }
+            }
+        }
+
+        let dom = render(app);
+        let inner = root_manager(&dom).inner;
+        let inner = inner.lock().unwrap();
+        assert_eq!(inner.instances.len(), 1);
+        assert_eq!(inner.instances[&BevyInstanceId::keyed("shared")].ref_count, 2);
+    }
+
+    #[test]
+    fn signal_values_outlive_the_pending_input_cap() {
+        fn app() -> Element {
+            rsx! {
+                BevyComponent { instance_id: "game", factory: unbuilt_factory() }
+            }
+        }
+
+        let dom = render(app);
+        let manager = root_manager(&dom);
+        let game = BevyInstanceId::keyed("game");
+        // No device yet, so nothing is built and everything waits
+        manager.send_signal(&game, SignalUpdate::F32("speed".to_string(), 1.0));
+        for i in 0..MAX_PENDING_INPUT + 10 {
+            let position = Vec2::new(i as f32, 0.0);
+            manager.send_message(&game, Box::new(BevyInputEvent::CursorMoved { position }));
+            manager.send_signal(&game, SignalUpdate::U32(format!("tile-{i}"), i as u32));
+        }
+        manager.send_message(&game, Box::new("level loaded"));
+        manager.send_signal(&game, SignalUpdate::F32("speed".to_string(), 2.0));
+
+        let inner = manager.inner.lock().unwrap();
+        let pending = &inner.pending_messages[&game];
+        let input: Vec<_> = pending.iter().filter_map(|msg| msg.downcast_ref::<BevyInputEvent>()).collect();
+        assert_eq!(input.len(), MAX_PENDING_INPUT);
+        assert_eq!(input[0], &BevyInputEvent::CursorMoved { position: Vec2::new(10.0, 0.0) });
+        assert_eq!(pending.back().unwrap().downcast_ref::<&str>(), Some(&"level loaded"));
+        assert!(!pending.iter().any(|msg| msg.is::<SignalUpdate>()));
+
+        // Every key keeps its latest value for the first build
+        let signals = &inner.instances[&game].signal_values;
+        assert_eq!(signals.len(), MAX_PENDING_INPUT + 11);
+        assert_eq!(signals["speed"].get::<f32>("speed"), Some(2.0));
+        assert_eq!(signals["tile-0"].get::<u32>("tile-0"), Some(0));
+    }
+
+    #[test]
+    fn event_hooks_follow_a_new_instance() {
+        fn app() -> Element {
+            let level = use_context_provider(|| Signal::new("first"));
+            let mut handled = use_context_provider(|| Signal::new(Vec::<u32>::new()));
+            let instance_id = BevyInstanceId::keyed(level());
+            let latest = use_bevy_event::<u32>(instance_id.clone());
+            use_context_provider(|| latest);
+            use_bevy_event_handler(instance_id.clone(), move |event: u32| handled.write().push(event));
+            rsx! {
+                BevyComponent { instance_id, factory: unbuilt_factory() }
+            }
+        }
+
+        let mut dom = render(app);
+        run_tasks(&mut dom);
+        let manager = root_manager(&dom);
+        let (first, second) = (BevyInstanceId::keyed("first"), BevyInstanceId::keyed("second"));
+        let (mut level, handled, latest) = dom.in_scope(ScopeId::APP, || {
+            (
+                consume_context::<Signal<&'static str>>(),
+                consume_context::<Signal<Vec<u32>>>(),
+                consume_context::<ReadSignal<Option<u32>>>(),
+            )
+        });
+        manager.event_bus(&first).emit(1_u32);
+        run_tasks(&mut dom);
+        assert_eq!(*latest.peek(), Some(1));
+
+        dom.in_scope(ScopeId::APP, || level.set("second"));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+        assert_eq!(*latest.peek(), None);
+
+        // Only the new instance's events come through
+        manager.event_bus(&first).emit(2_u32);
+        manager.event_bus(&second).emit(3_u32);
+        run_tasks(&mut dom);
+        assert_eq!(*latest.peek(), Some(3));
+        assert_eq!(*handled.peek(), vec![1, 3]);
+    }
+
+    #[test]
+    fn the_clear_color_is_only_sent_when_it_changes() {
+        fn app() -> Element {
+            let frame = use_context_provider(|| Signal::new(0));
+            let color = use_context_provider(|| Signal::new(Color::BLACK));
+            rsx! {
+                BevyComponent { instance_id: "scene", factory: unbuilt_factory(), clear_color: color(),
+                    p { "frame {frame}" }
+                }
+            }
+        }
+
+        let mut dom = render(app);
+        run_tasks(&mut dom);
+        let manager = root_manager(&dom);
+        let scene = BevyInstanceId::keyed("scene");
+        let sent = || {
+            let inner = manager.inner.lock().unwrap();
+            inner.pending_messages.get(&scene).map_or(0, |pending| pending.iter().filter(|msg| msg.is::<WorldUpdate>()).count())
+        };
+        let (mut frame, mut color) =
+            dom.in_scope(ScopeId::APP, || (consume_context::<Signal<i32>>(), consume_context::<Signal<Color>>()));
+        let mounted = sent();
+        assert!(mounted > 0);
+
+        // Rerenders for the children alone send nothing
+        dom.in_scope(ScopeId::APP, || frame.set(1));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+        assert_eq!(sent(), mounted);
+
+        dom.in_scope(ScopeId::APP, || color.set(Color::WHITE));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+        assert_eq!(sent(), mounted + 1);
+    }
+
+    #[test]
+    fn a_recycled_scope_starts_without_the_old_components_messages() {
+        fn app() -> Element {
+            rsx! {}
+        }
+
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        let window_renderer = DioxusNativeWindowRenderer::new();
+        let manager = BevyInstanceManager::with_retention_policy(RetentionPolicy::KeepForever);
+        let scope = BevyInstanceId::from(ScopeId::APP);
+        dom.in_scope(ScopeId::ROOT, || {
+            manager.get_or_create(scope.clone(), &window_renderer, |_: &DeviceHandle| unreachable!());
+            manager.send_message(&scope, Box::new("old component's update"));
+            manager.send_signal(&scope, SignalUpdate::F32("speed".to_string(), 1.0));
+            manager.release(&scope);
+
+            // Kept by the policy, but the scope id now belongs to a new component
+            manager.get_or_create(scope.clone(), &window_renderer, |_: &DeviceHandle| unreachable!());
+        });
+
+        let inner = manager.inner.lock().unwrap();
+        assert!(!inner.pending_messages.contains_key(&scope));
+        assert!(inner.instances[&scope].signal_values.is_empty());
+    }
+
+    #[test]
+    fn destroyed_instances_leave_no_event_state_behind() {
+        fn app() -> Element {
+            rsx! {}
+        }
+
+        let locked = PointerCapture { grab_mode: CursorGrabMode::Locked };
+        let released = PointerCapture { grab_mode: CursorGrabMode::None };
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        let window_renderer = DioxusNativeWindowRenderer::new();
+        let manager = BevyInstanceManager::new();
+        let (map, scope) = (BevyInstanceId::keyed("map"), BevyInstanceId::from(ScopeId::APP));
+        let mut listener = dom.in_scope(ScopeId::ROOT, || {
+            manager.get_or_create(map.clone(), &window_renderer, |_: &DeviceHandle| unreachable!());
+            manager.get_or_create(scope.clone(), &window_renderer, |_: &DeviceHandle| unreachable!());
+            manager.event_bus(&map).emit_state(locked);
+            manager.event_bus(&scope).emit_state(locked);
+            let listener = manager.event_bus(&scope).subscribe::<PointerCapture>();
+            manager.release(&map);
+            manager.release(&scope);
+            listener
+        });
+        assert_eq!(listener.next().now_or_never().flatten(), Some(locked));
+
+        // Nobody listens to the keyed bus anymore, so it goes
+        assert!(!manager.inner.lock().unwrap().event_buses.contains_key(&map));
+
+        // The scope's bus is still listened to, without the old app's states
+        let bus = manager.event_bus(&scope);
+        assert!(bus.subscribe::<PointerCapture>().next().now_or_never().is_none());
+        bus.emit_state(released);
+        assert_eq!(listener.next().now_or_never().flatten(), Some(released));
+    }
+
+    #[test]
+    fn dropping_the_manager_ends_its_eviction_task() {
+        fn app() -> Element {
+            rsx! {}
+        }
+
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild_in_place();
+        let manager = dom.in_scope(ScopeId::ROOT, || {
+            let manager = BevyInstanceManager::with_retention_policy(RetentionPolicy::KeepFor(Duration::from_secs(60)));
+            manager.spawn_eviction_task();
+            manager
+        });
+        let inner = Arc::downgrade(&manager.inner);
+        let mut run_tasks = || {
+            let tasks = Box::pin(dom.wait_for_work());
+            async_io::block_on(future::select(tasks, async_io::Timer::after(Duration::from_millis(20))));
+        };
+
+        // The task waits for wakeups without keeping the manager alive, and
+        // returns once its wakeup sender is dropped with it
+        run_tasks();
+        drop(manager);
+        assert_eq!(inner.strong_count(), 0);
+        run_tasks();
     }
 }
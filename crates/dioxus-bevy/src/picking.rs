@@ -0,0 +1,322 @@
+//! Entity picking on the Bevy canvas
+//!
+//! Feeds the forwarded pointer input to Bevy's picking backends and reports
+//! clicks and hovers back to Dioxus as `BevyPickEvent`s.
+
+use bevy::app::{App, Last, Plugin, PostUpdate};
+use bevy::asset::uuid::Uuid;
+use bevy::camera::NormalizedRenderTarget;
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::message::MessageReader;
+use bevy::ecs::name::Name;
+use bevy::ecs::query::With;
+use bevy::ecs::system::{Commands, Query, Res};
+use bevy::ecs::world::World;
+use bevy::input::mouse::MouseButton;
+use bevy::input::touch::TouchPhase;
+use bevy::input::ButtonState;
+use bevy::math::{Vec2, Vec3};
+use bevy::picking::backend::HitData;
+use bevy::picking::events::{Click, Out, Over, Pointer};
+use bevy::picking::input::PointerInputSettings;
+use bevy::picking::mesh_picking::MeshPickingPlugin;
+use bevy::picking::pointer::{Location, PointerAction, PointerButton, PointerId, PointerInput, PointerLocation};
+use std::collections::HashMap;
+
+use crate::events::DioxusEvents;
+use crate::input::BevyInputEvent;
+
+/// What happened to a picked entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BevyPickKind {
+    /// The entity was clicked
+    Click,
+    /// The pointer started hovering the entity
+    Over,
+    /// The pointer stopped hovering the entity
+    Out,
+}
+
+/// Picking result delivered to Dioxus
+///
+/// Received through the `onpick` (clicks) and `onhover` (over/out) handlers
+/// of `BevyComponent`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BevyPickEvent {
+    /// What happened to the entity
+    pub kind: BevyPickKind,
+    /// The picked entity
+    pub entity: Entity,
+    /// The entity's `Name`, if it has one
+    pub name: Option<String>,
+    /// World-space hit point, if the backend reports one
+    pub position: Option<Vec3>,
+    /// World-space surface normal at the hit point, if the backend reports one
+    pub normal: Option<Vec3>,
+}
+
+/// Adds mesh picking and reports pick results to Dioxus
+pub(crate) struct CanvasPickingPlugin;
+
+impl Plugin for CanvasPickingPlugin {
+    fn build(&self, app: &mut App) {
+        // Users may already have added the backend in their setup
+        if !app.is_plugin_added::<MeshPickingPlugin>() {
+            app.add_plugins(MeshPickingPlugin);
+        }
+        // Mouse and touch pointers are written from the forwarded canvas input;
+        // Bevy's own would locate them on the virtual window, which no camera renders to
+        if let Some(mut settings) = app.world_mut().get_resource_mut::<PointerInputSettings>() {
+            settings.is_mouse_enabled = false;
+            settings.is_touch_enabled = false;
+        }
+        app.init_resource::<DioxusEvents>()
+            .add_systems(PostUpdate, report_picks)
+            .add_systems(Last, despawn_lifted_touches);
+    }
+}
+
+/// Send picking events to the Dioxus side of the instance
+fn report_picks(
+    events: Res<DioxusEvents>,
+    names: Query<&Name>,
+    mut clicks: MessageReader<Pointer<Click>>,
+    mut overs: MessageReader<Pointer<Over>>,
+    mut outs: MessageReader<Pointer<Out>>,
+) {
+    let pick = |kind, entity: Entity, hit: &HitData| BevyPickEvent {
+        kind,
+        entity,
+        name: names.get(entity).ok().map(|name| name.as_str().to_string()),
+        position: hit.position,
+        normal: hit.normal,
+    };
+
+    for click in clicks.read() {
+        events.send(pick(BevyPickKind::Click, click.entity, &click.event.hit));
+    }
+    for over in overs.read() {
+        events.send(pick(BevyPickKind::Over, over.entity, &over.event.hit));
+    }
+    for out in outs.read() {
+        events.send(pick(BevyPickKind::Out, out.entity, &out.event.hit));
+    }
+}
+
+/// Pointer of a finger that lifted, despawned once its release has been handled
+#[derive(Component)]
+struct LiftedTouch;
+
+/// Despawn the pointers of lifted fingers, at the end of the frame that saw them lift
+fn despawn_lifted_touches(mut commands: Commands, lifted: Query<Entity, With<LiftedTouch>>) {
+    for entity in &lifted {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Upper bits of the custom pointer ids of viewports, the viewport id goes below
+const VIEWPORT_POINTERS: u128 = 0x6469_6f78_7573_2d62_6576_7900_0000_0000;
+
+/// Pointer driven by the forwarded canvas input
+///
+/// Cameras render into the canvas texture view rather than the virtual window,
+/// so picking input is written directly against that render target. The main
+/// canvas drives the mouse pointer; Bevy's own mouse input, which locates the
+/// pointer on the window, is turned off by `CanvasPickingPlugin` so this is the
+/// only one. Each viewport drives a custom pointer of its own, so hovering one
+/// canvas doesn't move the pointer of another. Every finger on a canvas drives
+/// a touch pointer, spawned when it touches down.
+#[derive(Default)]
+pub(crate) struct CanvasPointer {
+    id: PointerId,
+    /// Pointer entity spawned for a custom pointer
+    entity: Option<Entity>,
+    position: Vec2,
+    /// Pointer entity and position of each finger down
+    touches: HashMap<u64, (Entity, Vec2)>,
+}
+
+impl CanvasPointer {
+    /// Spawn the custom pointer of a viewport
+    pub(crate) fn spawn_custom(world: &mut World, viewport: u64) -> Self {
+        let id = PointerId::Custom(Uuid::from_u128(VIEWPORT_POINTERS | viewport as u128));
+        Self {
+            id,
+            entity: Some(world.spawn(id).id()),
+            ..Default::default()
+        }
+    }
+
+    /// Despawn the pointer entities of a custom pointer and of the fingers down
+    pub(crate) fn despawn(&self, world: &mut World) {
+        for entity in self.entity.iter().chain(self.touches.values().map(|(entity, _)| entity)) {
+            world.despawn(*entity);
+        }
+    }
+
+    /// Write the picking input for a forwarded input event
+    ///
+    /// Texture view targets have a scale factor of 1, so locations are in physical pixels.
+    pub(crate) fn apply(
+        &mut self,
+        world: &mut World,
+        target: NormalizedRenderTarget,
+        scale_factor: f32,
+        event: &BevyInputEvent,
+    ) {
+        if let BevyInputEvent::Touch { id, phase, position, .. } = *event {
+            self.apply_touch(world, target, id, phase, position * scale_factor);
+            return;
+        }
+
+        let action = match *event {
+            BevyInputEvent::CursorMoved { position } => {
+                let position = position * scale_factor;
+                let delta = position - self.position;
+                self.position = position;
+                PointerAction::Move { delta }
+            }
+            BevyInputEvent::MouseButton { button, state } => {
+                let button = match button {
+                    MouseButton::Left => PointerButton::Primary,
+                    MouseButton::Right => PointerButton::Secondary,
+                    MouseButton::Middle => PointerButton::Middle,
+                    _ => return,
+                };
+                match state {
+                    ButtonState::Pressed => PointerAction::Press(button),
+                    ButtonState::Released => PointerAction::Release(button),
+                }
+            }
+            BevyInputEvent::MouseWheel { unit, delta } => PointerAction::Scroll {
+                unit,
+                x: delta.x,
+                y: delta.y,
+            },
+            BevyInputEvent::CursorLeft => {
+                // Park the pointer outside every viewport so hovered entities get `Out`
+                let position = Vec2::splat(-1.0);
+                let delta = position - self.position;
+                self.position = position;
+                PointerAction::Move { delta }
+            }
+            _ => return,
+        };
+
+        world.write_message(PointerInput::new(
+            self.id,
+            Location {
+                target,
+                position: self.position,
+            },
+            action,
+        ));
+    }
+
+    /// Write the picking input for a finger, as Bevy does for window touches
+    fn apply_touch(
+        &mut self,
+        world: &mut World,
+        target: NormalizedRenderTarget,
+        id: u64,
+        phase: TouchPhase,
+        position: Vec2,
+    ) {
+        let pointer = PointerId::Touch(id);
+        let location = Location { target, position };
+        let action = match phase {
+            TouchPhase::Started => {
+                let entity = world.spawn((pointer, PointerLocation::new(location.clone()))).id();
+                self.touches.insert(id, (entity, position));
+                PointerAction::Press(PointerButton::Primary)
+            }
+            TouchPhase::Moved => {
+                let Some((_, last)) = self.touches.get_mut(&id) else {
+                    return;
+                };
+                if *last == position {
+                    return;
+                }
+                let delta = position - *last;
+                *last = position;
+                PointerAction::Move { delta }
+            }
+            TouchPhase::Ended | TouchPhase::Canceled => {
+                let Some((entity, _)) = self.touches.remove(&id) else {
+                    return;
+                };
+                world.entity_mut(entity).insert(LiftedTouch);
+                match phase {
+                    TouchPhase::Ended => PointerAction::Release(PointerButton::Primary),
+                    _ => PointerAction::Cancel,
+                }
+            }
+        };
+        world.write_message(PointerInput::new(pointer, location, action));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::BevyEventBus;
+    use bevy::asset::Assets;
+    use bevy::camera::ManualTextureViewHandle;
+    use bevy::ecs::message::MessageWriter;
+    use bevy::ecs::resource::Resource;
+    use bevy::ecs::schedule::IntoScheduleConfigs;
+    use bevy::picking::backend::PointerHits;
+    use bevy::picking::{DefaultPickingPlugins, PickingSystems};
+    use bevy::mesh::Mesh;
+    use bevy::prelude::{MinimalPlugins, PreUpdate};
+    use futures_util::{FutureExt, StreamExt};
+
+    /// Entity every pointer is over, seen through `camera`
+    #[derive(Resource)]
+    struct Under {
+        entity: Entity,
+        camera: Entity,
+    }
+
+    /// Backend hitting `Under` wherever a pointer is
+    fn hit_under(under: Res<Under>, pointers: Query<&PointerId>, mut hits: MessageWriter<PointerHits>) {
+        for pointer in &pointers {
+            let hit = HitData::new(under.camera, 1.0, None, None);
+            hits.write(PointerHits::new(*pointer, vec![(under.entity, hit)], 0.0));
+        }
+    }
+
+    #[test]
+    fn touches_pick_entities() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, DefaultPickingPlugins, CanvasPickingPlugin))
+            .add_systems(PreUpdate, hit_under.in_set(PickingSystems::Backend));
+        let world = app.world_mut();
+        // The mesh backend runs too, with no meshes to hit
+        world.init_resource::<Assets<Mesh>>();
+        let camera = world.spawn_empty().id();
+        let entity = world.spawn(Name::new("crate")).id();
+        world.insert_resource(Under { entity, camera });
+        let bus = BevyEventBus::default();
+        let mut picks = bus.subscribe::<BevyPickEvent>();
+        world.resource::<DioxusEvents>().attach(bus);
+        app.update();
+
+        let target = NormalizedRenderTarget::TextureView(ManualTextureViewHandle(1));
+        let mut pointer = CanvasPointer::default();
+        let touch = |phase| BevyInputEvent::Touch { id: 4, phase, position: Vec2::new(20.0, 10.0), force: 1.0 };
+        pointer.apply(app.world_mut(), target.clone(), 2.0, &touch(TouchPhase::Started));
+        app.update();
+        pointer.apply(app.world_mut(), target, 2.0, &touch(TouchPhase::Ended));
+        app.update();
+
+        let picks: Vec<_> = std::iter::from_fn(|| picks.next().now_or_never().flatten()).collect();
+        assert!(picks.iter().any(|pick| pick.kind == BevyPickKind::Click && pick.entity == entity));
+        assert_eq!(picks[0].name.as_deref(), Some("crate"));
+
+        // The finger's pointer goes once it lifted
+        let mut pointers = app.world_mut().query::<&PointerId>();
+        assert!(!pointers.iter(app.world()).any(|pointer| pointer.is_touch()));
+    }
+}
@@ -8,8 +8,8 @@
 //! use dioxus_bevy::prelude::*;
 //! ```
 
-// Main component
-pub use crate::BevyComponent;
+// Main components
+pub use crate::{BevyAnchor, BevyComponent, BevyViewport};
 
 // Procedural macro
 pub use crate::bevy_component;
@@ -17,11 +17,33 @@ pub use crate::bevy_component;
 // Core renderer trait
 pub use crate::BevyRenderer;
 
+// Canvas input forwarding, virtual window, viewport and cameras
+pub use crate::{BevyInputEvent, CanvasCamera, CanvasViewport, CanvasWindow, PointerCapture, TouchGestures};
+
+// Canvas texture format and compositing
+pub use crate::{CanvasAlphaMode, CanvasFormat, ResolveTonemap};
+
+// Multiple views of one app
+pub use crate::{ActiveViewport, ViewportCamera, ViewportWindow};
+
+// Picking results
+pub use crate::{BevyPickEvent, BevyPickKind};
+
+// Anchored elements
+pub use crate::AnchorOcclusion;
+
 // Message passing system
 pub use crate::{
+    use_bevy_binding,
+    use_bevy_event,
+    use_bevy_event_handler,
     use_bevy_message,
+    use_bevy_prop,
     BevyMessageSender,
+    BevyProp,
+    DioxusEvents,
     SignalUpdate,
+    SignalValue,
     SignalReceiver,
 };
 
@@ -31,5 +53,8 @@ pub use crate::{
     asset_path,
 };
 
-// Convenience type alias
+// Instance identity
 pub use crate::BevyInstanceId;
+
+// Instance lifecycle
+pub use crate::{BevyCarryOver, BevyInstanceManager, RebuildKey, RetentionPolicy};
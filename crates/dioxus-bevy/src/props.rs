@@ -0,0 +1,257 @@
+//! Dioxus props mirrored into Bevy resources
+//!
+//! `#[bevy_component]` generates a resource type for props that ask for one and
+//! uses the hooks here to keep it in sync with the Dioxus side. Read-only signal
+//! props marked `#[resource]` flow one way into Bevy, so systems can take
+//! `Res<...>` and check `is_changed()`.
+//! Writable `Signal<T>` props marked `#[bind]` are bound both ways: Bevy systems
+//! mutating the resource write the value back to the signal on the next frame.
+
+use bevy::app::{App, Last};
+use bevy::ecs::change_detection::DetectChanges;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::system::{Res, ResMut};
+use dioxus::prelude::*;
+use dioxus_core::Task;
+use futures_util::StreamExt;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::events::DioxusEvents;
+use crate::BevyInstanceId;
+
+/// Bevy resource wrapping the value of a component prop
+///
+/// Implemented by `#[bevy_component]` for the resource it generates for each prop.
+pub trait BevyProp: Resource + Clone + PartialEq {
+    /// Value type of the Dioxus prop
+    type Value: Clone + PartialEq + Send + Sync + 'static;
+
+    /// Wrap a prop value in the resource
+    fn from_value(value: Self::Value) -> Self;
+
+    /// Unwrap the prop value from the resource
+    fn into_value(self) -> Self::Value;
+}
+
+/// Last value of a bound prop that both Dioxus and Bevy agree on
+///
+/// Lets the sync system tell Bevy-side mutations apart from values that just
+/// arrived from Dioxus, so they aren't echoed back.
+#[derive(Resource)]
+struct SyncedProp<P: BevyProp>(P);
+
+/// Hook mirroring a read-only Dioxus signal into a Bevy prop resource
+///
+/// Every change of the signal replaces the resource between two frames, so it
+/// shows up as changed to systems in the next update. Values equal to the
+/// current resource are dropped to keep change detection quiet. A new
+/// `instance_id` gets the current value right away.
+///
+/// Returns the latest value of the signal, for the factory to start rebuilt
+/// apps from.
+pub fn use_bevy_prop<P: BevyProp>(instance_id: BevyInstanceId, signal: ReadSignal<P::Value>) -> Arc<Mutex<P::Value>> {
+    let sender = crate::use_bevy_message(instance_id);
+    let latest = use_hook(|| Arc::new(Mutex::new((*signal.peek()).clone())));
+    use_effect(use_reactive((&sender,), {
+        let latest = latest.clone();
+        move |(sender,)| {
+            let value = signal();
+            *latest.lock().unwrap() = value.clone();
+            let prop = P::from_value(value);
+            sender.send_world_update(move |world| {
+                if world.get_resource::<P>() != Some(&prop) {
+                    world.insert_resource(prop);
+                }
+            });
+        }
+    }));
+    latest
+}
+
+/// Insert a two-way bound prop resource and the system writing it back to Dioxus
+pub fn bind_prop<P: BevyProp>(app: &mut App, initial: P) {
+    app.insert_resource(SyncedProp(initial.clone()));
+    app.insert_resource(initial);
+    app.add_systems(Last, sync_prop_to_dioxus::<P>);
+}
+
+/// Send a bound prop to Dioxus when a Bevy system changed it
+fn sync_prop_to_dioxus<P: BevyProp>(
+    prop: Res<P>,
+    mut synced: ResMut<SyncedProp<P>>,
+    events: Res<DioxusEvents>,
+) {
+    if prop.is_changed() && *prop != synced.0 {
+        synced.0 = prop.clone();
+        events.send(prop.clone());
+    }
+}
+
+/// Hook binding a writable Dioxus signal to a Bevy prop resource
+///
+/// Signal writes are mirrored into the resource, and Bevy-side mutations of the
+/// resource are written back to the signal. Values equal to the current one are
+/// dropped on both sides, and values written back from Bevy aren't sent to it
+/// again, so an update never bounces back and forth or undoes a newer Bevy-side
+/// change. A new `instance_id` gets the current value, and its resource is
+/// followed instead.
+///
+/// Returns the latest value of the signal, for the factory to start rebuilt
+/// apps from.
+pub fn use_bevy_binding<P: BevyProp>(instance_id: BevyInstanceId, mut signal: Signal<P::Value>) -> Arc<Mutex<P::Value>> {
+    let manager = crate::use_instance_manager();
+
+    // Last value taken from each instance, not to be sent back to it
+    let from_bevy = use_hook(|| Rc::new(Cell::new(None::<(BevyInstanceId, P::Value)>)));
+
+    // Dioxus -> Bevy
+    let sender = crate::use_bevy_message(instance_id.clone());
+    let latest = use_hook(|| Arc::new(Mutex::new((*signal.peek()).clone())));
+    use_effect(use_reactive((&sender, &instance_id), {
+        let latest = latest.clone();
+        let from_bevy = from_bevy.clone();
+        move |(sender, instance_id)| {
+            let value = signal();
+            *latest.lock().unwrap() = value.clone();
+            // Bevy may have changed it again since, which the echo would undo
+            if from_bevy.take() == Some((instance_id, value.clone())) {
+                return;
+            }
+            let prop = P::from_value(value);
+            sender.send_world_update(move |world| {
+                if world.get_resource::<P>() != Some(&prop) {
+                    world.insert_resource(SyncedProp(prop.clone()));
+                    world.insert_resource(prop);
+                }
+            });
+        }
+    }));
+
+    // Bevy -> Dioxus
+    let updates_task = use_hook(|| Rc::new(Cell::new(None::<Task>)));
+    use_effect(use_reactive((&instance_id,), move |(instance_id,)| {
+        let mut updates = manager.peek().event_bus(&instance_id).subscribe::<P>();
+        let from_bevy = from_bevy.clone();
+        let task = spawn(async move {
+            while let Some(prop) = updates.next().await {
+                let value = prop.into_value();
+                if *signal.peek() != value {
+                    from_bevy.set(Some((instance_id.clone(), value.clone())));
+                    signal.set(value);
+                }
+            }
+        });
+        if let Some(previous) = updates_task.replace(Some(task)) {
+            previous.cancel();
+        }
+    }));
+    latest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{render, root_manager, run_tasks, unbuilt_factory};
+    use crate::{BevyComponent, WorldUpdate};
+    use bevy::ecs::world::World;
+
+    #[derive(Resource, Clone, PartialEq)]
+    struct Zoom(u32);
+
+    impl BevyProp for Zoom {
+        type Value = u32;
+
+        fn from_value(value: u32) -> Self {
+            Self(value)
+        }
+
+        fn into_value(self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn bindings_follow_a_new_instance() {
+        fn app() -> Element {
+            let level = use_context_provider(|| Signal::new("first"));
+            let zoom = use_context_provider(|| Signal::new(1_u32));
+            let instance_id = BevyInstanceId::keyed(level());
+            use_bevy_binding::<Zoom>(instance_id.clone(), zoom);
+            rsx! {
+                BevyComponent { instance_id, factory: unbuilt_factory() }
+            }
+        }
+
+        let mut dom = render(app);
+        run_tasks(&mut dom);
+        let manager = root_manager(&dom);
+        let (first, second) = (BevyInstanceId::keyed("first"), BevyInstanceId::keyed("second"));
+        let (mut level, zoom) = dom.in_scope(ScopeId::APP, || {
+            (consume_context::<Signal<&'static str>>(), consume_context::<Signal<u32>>())
+        });
+
+        dom.in_scope(ScopeId::APP, || level.set("second"));
+        run_tasks(&mut dom);
+        run_tasks(&mut dom);
+
+        // The new instance gets the current value
+        let sent = |id: &BevyInstanceId| {
+            let inner = manager.inner.lock().unwrap();
+            inner.pending_messages.get(id).is_some_and(|pending| pending.iter().any(|msg| msg.is::<WorldUpdate>()))
+        };
+        assert!(sent(&second));
+
+        // And Bevy-side changes are taken from it only
+        manager.event_bus(&first).emit(Zoom(5));
+        run_tasks(&mut dom);
+        assert_eq!(*zoom.peek(), 1);
+        manager.event_bus(&second).emit(Zoom(3));
+        run_tasks(&mut dom);
+        assert_eq!(*zoom.peek(), 3);
+    }
+
+    #[test]
+    fn bevy_side_changes_are_not_echoed_back() {
+        fn app() -> Element {
+            let zoom = use_context_provider(|| Signal::new(1_u32));
+            let instance_id = BevyInstanceId::keyed("map");
+            use_bevy_binding::<Zoom>(instance_id.clone(), zoom);
+            rsx! {
+                BevyComponent { instance_id, factory: unbuilt_factory() }
+            }
+        }
+
+        let mut dom = render(app);
+        run_tasks(&mut dom);
+        let manager = root_manager(&dom);
+        let map = BevyInstanceId::keyed("map");
+        let zoom = dom.in_scope(ScopeId::APP, consume_context::<Signal<u32>>);
+        let mut world = World::new();
+        let apply_sent = |world: &mut World| {
+            let pending = manager.inner.lock().unwrap().pending_messages.remove(&map).unwrap_or_default();
+            for msg in pending {
+                if let Ok(update) = msg.downcast::<WorldUpdate>() {
+                    (update.0)(world);
+                }
+            }
+        };
+        apply_sent(&mut world);
+        assert_eq!(world.resource::<Zoom>().0, 1);
+
+        // Bevy zooms twice, and Dioxus only gets the first one before the next frame
+        world.insert_resource(Zoom(3));
+        manager.event_bus(&map).emit(Zoom(2));
+        run_tasks(&mut dom);
+        assert_eq!(*zoom.peek(), 2);
+        apply_sent(&mut world);
+        assert_eq!(world.resource::<Zoom>().0, 3);
+
+        manager.event_bus(&map).emit(Zoom(3));
+        run_tasks(&mut dom);
+        assert_eq!(*zoom.peek(), 3);
+        apply_sent(&mut world);
+        assert_eq!(world.resource::<Zoom>().0, 3);
+    }
+}
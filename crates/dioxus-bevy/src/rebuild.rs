@@ -0,0 +1,95 @@
+//! Rebuilding a Bevy instance with a new factory
+//!
+//! Factories are closures and can't be compared, so a new `factory` alone never
+//! restarts the running app. A `rebuild_on` key makes the change explicit: when
+//! it differs from the previous render, the renderer is shut down and the new
+//! factory runs on the same device, optionally keeping some resources.
+
+use bevy::ecs::resource::Resource;
+use bevy::ecs::world::World;
+use std::any::TypeId;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::{BevyRenderer, WorldUpdate};
+
+/// Value whose changes rebuild a `BevyComponent`
+///
+/// Built from anything hashable, e.g. the name of the selected scene. The
+/// default key is the one of a component without `rebuild_on`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebuildKey(Option<u64>);
+
+impl<T: Hash> From<T> for RebuildKey {
+    fn from(value: T) -> Self {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        RebuildKey(Some(hasher.finish()))
+    }
+}
+
+/// Copies a resource out of one world into an update for another
+type Extractor = Arc<dyn Fn(&World) -> WorldUpdate + Send + Sync>;
+
+/// Resources kept across a rebuild
+///
+/// Each listed resource is cloned out of the old app before it shuts down and
+/// inserted into the new one after its setup has run. Two carry-overs are equal
+/// when they list the same resources in the same order.
+///
+/// # Example
+/// ```rust,ignore
+/// BevyComponent {
+///     instance_id,
+///     factory,
+///     rebuild_on: selected_scene(),
+///     carry_over: BevyCarryOver::new().resource::<CameraRig>(),
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct BevyCarryOver {
+    extractors: Vec<(TypeId, Extractor)>,
+}
+
+impl PartialEq for BevyCarryOver {
+    fn eq(&self, other: &Self) -> bool {
+        self.extractors.iter().map(|(id, _)| id).eq(other.extractors.iter().map(|(id, _)| id))
+    }
+}
+
+impl BevyCarryOver {
+    /// Carry nothing over
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also carry over resource `R`, if the old app has it
+    pub fn resource<R: Resource + Clone>(mut self) -> Self {
+        let extract: Extractor = Arc::new(|world: &World| {
+            let value = world.get_resource::<R>().cloned();
+            WorldUpdate(Box::new(move |world: &mut World| {
+                if let Some(value) = value {
+                    world.insert_resource(value);
+                }
+            }))
+        });
+        self.extractors.push((TypeId::of::<R>(), extract));
+        self
+    }
+
+    /// Copy the listed resources out of a renderer's world
+    ///
+    /// Goes through a `WorldUpdate` message, so renderers that don't handle
+    /// those yield nothing.
+    pub(crate) fn extract(&self, renderer: &mut dyn BevyRenderer) -> Vec<WorldUpdate> {
+        let extracted = Arc::new(Mutex::new(Vec::new()));
+        let extractors = self.extractors.clone();
+        let out = extracted.clone();
+        renderer.handle_message(Box::new(WorldUpdate(Box::new(move |world: &mut World| {
+            let updates = extractors.iter().map(|(_, extract)| extract(world)).collect();
+            *out.lock().unwrap() = updates;
+        }))));
+        let updates = std::mem::take(&mut *extracted.lock().unwrap());
+        updates
+    }
+}
@@ -0,0 +1,447 @@
+//! Render target textures of the canvases
+//!
+//! Bevy renders into a `ManualTextureView`, Dioxus composites an 8-bit sRGB
+//! texture with straight alpha. When Bevy's output already is that (`Sdr`
+//! format, `Straight` or no `CanvasAlphaMode`) they are the same texture. Otherwise Bevy renders
+//! into an intermediate texture, and after each update a resolve pass
+//! tonemaps and unpremultiplies it into the texture Dioxus shows.
+
+use bevy::camera::ManualTextureViewHandle;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::world::World;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::texture::{ManualTextureView, ManualTextureViews};
+use wgpu::util::DeviceExt;
+
+/// Format of the texture Bevy renders the canvas into
+///
+/// Insert it as a resource during setup; it is read whenever a canvas texture
+/// is created, and changing it recreates them on the next render.
+///
+/// HDR cameras (`Hdr`, bloom, auto-exposure) work with either format, since
+/// Bevy tonemaps them into its output. The float target keeps values above 1
+/// past that output, for cameras with `Tonemapping::None` or post-processing
+/// that needs the extra range, and leaves the final mapping to `tonemap`.
+///
+/// # Example
+/// ```rust,ignore
+/// app.insert_resource(CanvasFormat::Hdr { tonemap: ResolveTonemap::AcesFitted });
+/// app.add_systems(Startup, |mut commands: Commands| {
+///     commands.spawn((Camera3d::default(), Hdr, Tonemapping::None, Bloom::NATURAL));
+/// });
+/// ```
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CanvasFormat {
+    /// 8-bit sRGB, the format Dioxus composites
+    #[default]
+    Sdr,
+    /// 16-bit float, resolved into 8-bit sRGB for Dioxus
+    Hdr {
+        /// Mapping of the float values into the displayable range
+        tonemap: ResolveTonemap,
+    },
+}
+
+/// How the alpha of Bevy's output is interpreted when compositing over Dioxus
+///
+/// Insert it as a resource during setup, like `CanvasFormat`. Colors are
+/// (un)premultiplied in linear space, before the sRGB encoding of the texture
+/// Dioxus composites.
+///
+/// Without the resource an `Sdr` canvas is shown as Bevy rendered it, with no
+/// resolve pass, and an `Hdr` canvas is resolved as `Premultiplied`.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CanvasAlphaMode {
+    /// Ignore alpha; the canvas covers what is behind it
+    Opaque,
+    /// Color is premultiplied by alpha, as produced by Bevy's alpha blending
+    /// over a transparent `ClearColor`
+    ///
+    /// Unpremultiplied for Dioxus, which avoids dark fringes on anti-aliased
+    /// and translucent edges.
+    #[default]
+    Premultiplied,
+    /// Color is independent of alpha, e.g. written by custom shaders; passed
+    /// to Dioxus as is
+    Straight,
+}
+
+impl CanvasAlphaMode {
+    /// Mode index in the resolve shader
+    fn index(self) -> u32 {
+        match self {
+            CanvasAlphaMode::Opaque => 0,
+            CanvasAlphaMode::Premultiplied => 1,
+            CanvasAlphaMode::Straight => 2,
+        }
+    }
+}
+
+/// Tonemapping applied when resolving an HDR canvas for Dioxus
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResolveTonemap {
+    /// Clamp to [0, 1], for output Bevy already tonemapped
+    #[default]
+    Clamp,
+    /// Reinhard, `c / (1 + c)`
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve
+    AcesFitted,
+}
+
+impl ResolveTonemap {
+    /// Operator index in the resolve shader
+    fn index(self) -> u32 {
+        match self {
+            ResolveTonemap::Clamp => 0,
+            ResolveTonemap::Reinhard => 1,
+            ResolveTonemap::AcesFitted => 2,
+        }
+    }
+}
+
+/// Format of the textures Dioxus composites
+const OUTPUT_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Format Bevy renders into with `CanvasFormat::Hdr`
+const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Settings the canvas textures of an app are created with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TargetConfig {
+    format: CanvasFormat,
+    /// `None` when the app did not insert a `CanvasAlphaMode`
+    alpha_mode: Option<CanvasAlphaMode>,
+}
+
+impl TargetConfig {
+    /// Read the settings from the app's resources
+    pub(crate) fn of(world: &World) -> Self {
+        Self {
+            format: world.get_resource::<CanvasFormat>().copied().unwrap_or_default(),
+            alpha_mode: world.get_resource::<CanvasAlphaMode>().copied(),
+        }
+    }
+
+    /// Whether Bevy's output needs converting before Dioxus can show it
+    fn needs_resolve(&self) -> bool {
+        self.format != CanvasFormat::Sdr
+            || self.alpha_mode.is_some_and(|mode| mode != CanvasAlphaMode::Straight)
+    }
+}
+
+/// Textures behind one canvas
+pub(crate) struct TargetTextures {
+    /// Texture registered with Dioxus
+    pub(crate) output: wgpu::Texture,
+    config: TargetConfig,
+    /// Intermediate texture Bevy renders into, and its resolve bindings
+    intermediate: Option<Intermediate>,
+}
+
+struct Intermediate {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+impl TargetTextures {
+    /// Settings the textures were created with
+    pub(crate) fn config(&self) -> TargetConfig {
+        self.config
+    }
+
+    /// GPU memory held by the textures, in bytes
+    pub(crate) fn byte_size(&self) -> u64 {
+        let pixels = self.output.width() as u64 * self.output.height() as u64;
+        let intermediate = match (&self.intermediate, self.config.format) {
+            (None, _) => 0,
+            (Some(_), CanvasFormat::Sdr) => 4,
+            (Some(_), CanvasFormat::Hdr { .. }) => 8,
+        };
+        pixels * (4 + intermediate)
+    }
+
+    /// Free the textures' memory right away
+    pub(crate) fn destroy(&self) {
+        self.output.destroy();
+        if let Some(intermediate) = &self.intermediate {
+            intermediate.texture.destroy();
+        }
+    }
+}
+
+/// Create the textures of a canvas and make them the texture view `handle`
+///
+/// Replaces any view previously under `handle`. Returns `None` if the app has
+/// no `ManualTextureViews`.
+pub(crate) fn create_target(
+    device: &wgpu::Device,
+    resolve: &mut Option<CanvasResolve>,
+    world: &mut World,
+    handle: ManualTextureViewHandle,
+    width: u32,
+    height: u32,
+) -> Option<TargetTextures> {
+    let config = TargetConfig::of(world);
+    let mut manual_texture_views = world.get_resource_mut::<ManualTextureViews>()?;
+
+    let output = create_texture(device, OUTPUT_FORMAT, width, height);
+    let (render_texture, intermediate) = if config.needs_resolve() {
+        let format = match config.format {
+            CanvasFormat::Sdr => OUTPUT_FORMAT,
+            CanvasFormat::Hdr { .. } => HDR_FORMAT,
+        };
+        let texture = create_texture(device, format, width, height);
+        let resolve = resolve.get_or_insert_with(|| CanvasResolve::new(device));
+        let bind_group = resolve.bind(device, &texture, config);
+        (texture.clone(), Some(Intermediate { texture, bind_group }))
+    } else {
+        (output.clone(), None)
+    };
+
+    let manual_texture_view = ManualTextureView {
+        texture_view: render_texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+            .into(),
+        size: bevy::math::UVec2::new(width, height),
+        format: render_texture.format(),
+    };
+    manual_texture_views.insert(handle, manual_texture_view);
+
+    Some(TargetTextures {
+        output,
+        config,
+        intermediate,
+    })
+}
+
+fn create_texture(device: &wgpu::Device, format: TextureFormat, width: u32, height: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("bevy_texture"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::TEXTURE_BINDING
+            | TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+const RESOLVE_SHADER: &str = r#"
+struct Resolve {
+    tonemap: u32,
+    alpha_mode: u32,
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> resolve: Resolve;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // Triangle covering the whole target
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(source, vec2<i32>(position.xy), 0);
+    var alpha = clamp(color.a, 0.0, 1.0);
+    var c = max(color.rgb, vec3<f32>(0.0));
+    // Dioxus composites straight alpha
+    if resolve.alpha_mode == 1u && alpha > 0.0 {
+        c = c / alpha;
+    }
+    if resolve.alpha_mode == 0u {
+        alpha = 1.0;
+    }
+    var mapped: vec3<f32>;
+    switch resolve.tonemap {
+        case 1u: {
+            mapped = c / (c + vec3<f32>(1.0));
+        }
+        case 2u: {
+            mapped = (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14);
+        }
+        default: {
+            mapped = c;
+        }
+    }
+    return vec4<f32>(clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0)), alpha);
+}
+"#;
+
+/// Pipeline converting intermediate canvas textures into their outputs
+pub(crate) struct CanvasResolve {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+}
+
+impl CanvasResolve {
+    fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            source: wgpu::ShaderSource::Wgsl(RESOLVE_SHADER.into()),
+        });
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment"),
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: OUTPUT_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+            cache: None,
+        });
+        Self { pipeline, layout }
+    }
+
+    /// Bind an intermediate texture for resolving with `config`
+    fn bind(&self, device: &wgpu::Device, source: &wgpu::Texture, config: TargetConfig) -> wgpu::BindGroup {
+        let tonemap = match config.format {
+            CanvasFormat::Sdr => ResolveTonemap::Clamp,
+            CanvasFormat::Hdr { tonemap } => tonemap,
+        };
+        // Padded to the 16 byte uniform alignment
+        let mut params = [0u8; 16];
+        let alpha_mode = config.alpha_mode.unwrap_or_default();
+        params[..4].copy_from_slice(&tonemap.index().to_le_bytes());
+        params[4..8].copy_from_slice(&alpha_mode.index().to_le_bytes());
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let view = source.create_view(&wgpu::TextureViewDescriptor::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Resolve the canvases among `targets` that have an intermediate texture
+    pub(crate) fn run<'a>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        targets: impl Iterator<Item = &'a TargetTextures>,
+    ) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("dioxus_bevy_canvas_resolve"),
+        });
+        let mut resolved = false;
+        for target in targets {
+            let Some(intermediate) = &target.intermediate else {
+                continue;
+            };
+            let output = target.output.create_view(&wgpu::TextureViewDescriptor::default());
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("dioxus_bevy_canvas_resolve"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &output,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &intermediate.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+            resolved = true;
+        }
+        if resolved {
+            queue.submit([encoder.finish()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(format: CanvasFormat, alpha_mode: Option<CanvasAlphaMode>) -> TargetConfig {
+        TargetConfig { format, alpha_mode }
+    }
+
+    #[test]
+    fn only_explicit_conversions_need_a_resolve_pass() {
+        assert!(!config(CanvasFormat::Sdr, None).needs_resolve());
+        assert!(!config(CanvasFormat::Sdr, Some(CanvasAlphaMode::Straight)).needs_resolve());
+        assert!(config(CanvasFormat::Sdr, Some(CanvasAlphaMode::Premultiplied)).needs_resolve());
+        assert!(config(CanvasFormat::Sdr, Some(CanvasAlphaMode::Opaque)).needs_resolve());
+
+        let hdr = CanvasFormat::Hdr {
+            tonemap: ResolveTonemap::Clamp,
+        };
+        assert!(config(hdr, None).needs_resolve());
+        assert!(config(hdr, Some(CanvasAlphaMode::Straight)).needs_resolve());
+    }
+}
@@ -0,0 +1,586 @@
+//! Several canvases onto one Bevy world
+//!
+//! A `BevyViewport` shows one camera of a shared app in its own canvas. Each
+//! viewport gets its own texture view, sized to its canvas, and its input is
+//! routed to its own picking pointer, virtual window and the camera of that
+//! view. The app still
+//! updates once per frame, however many views it renders into.
+
+use bevy::camera::{Camera, ManualTextureViewHandle, NormalizedRenderTarget, RenderTarget};
+use bevy::ecs::change_detection::DetectChangesMut;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::name::Name;
+use bevy::ecs::query::With;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::world::World;
+use bevy::math::UVec2;
+use bevy::render::texture::ManualTextureViews;
+use dioxus::prelude::*;
+use dioxus_core::use_hook_with_cleanup;
+use dioxus_native::{CustomPaintCtx, CustomPaintSource, DeviceHandle, DioxusNativeWindowRenderer, TextureHandle};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::camera::{self, CanvasTarget, CANVAS_TEXTURE_VIEW};
+use crate::input::{BevyInputEvent, CanvasInputState};
+use crate::picking::CanvasPointer;
+use crate::{canvas, cursor, keyed_body, replay_pending, window, use_instance_manager, BevyInstanceId, BevyInstanceManager, BevyInstanceManagerInner, RendererFactory};
+
+/// Identifies a viewport within its app
+pub(crate) type ViewportId = u64;
+
+static NEXT_VIEWPORT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Camera shown by a `BevyViewport`
+///
+/// Cameras picked by name are looked up every frame, so the viewport follows a
+/// camera spawned later or replaced under the same `Name`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewportCamera {
+    /// A specific camera entity
+    Entity(Entity),
+    /// The camera with this `Name`
+    Name(String),
+}
+
+impl ViewportCamera {
+    /// Find the camera entity in the world
+    pub(crate) fn resolve(&self, world: &mut World) -> Option<Entity> {
+        match self {
+            ViewportCamera::Entity(entity) => world.get::<Camera>(*entity).map(|_| *entity),
+            ViewportCamera::Name(name) => world
+                .query_filtered::<(Entity, &Name), With<Camera>>()
+                .iter(world)
+                .find(|(_, camera_name)| camera_name.as_str() == name)
+                .map(|(entity, _)| entity),
+        }
+    }
+}
+
+impl From<Entity> for ViewportCamera {
+    fn from(entity: Entity) -> Self {
+        ViewportCamera::Entity(entity)
+    }
+}
+
+impl From<&str> for ViewportCamera {
+    fn from(name: &str) -> Self {
+        ViewportCamera::Name(name.to_string())
+    }
+}
+
+impl From<String> for ViewportCamera {
+    fn from(name: String) -> Self {
+        ViewportCamera::Name(name)
+    }
+}
+
+/// Camera and window of the viewport the pointer is over
+///
+/// Lets systems such as camera controllers act on the view being used. `None`
+/// while the pointer is over the main canvas or outside every viewport.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActiveViewport {
+    /// Camera rendered into that viewport
+    pub camera: Option<Entity>,
+    /// `ViewportWindow` of that viewport, whose cursor is in the camera's pixels
+    pub window: Option<Entity>,
+}
+
+/// Viewport registration sent to the renderer
+pub(crate) enum ViewportMessage {
+    /// Start rendering a camera into a viewport, or switch its camera
+    Add { id: ViewportId, camera: ViewportCamera },
+    /// Stop rendering into a viewport and free its texture
+    Remove { id: ViewportId },
+}
+
+/// Input over a viewport's canvas
+pub(crate) struct ViewportInput {
+    pub(crate) viewport: ViewportId,
+    pub(crate) event: BevyInputEvent,
+}
+
+/// Render target and input state of one viewport, kept by the renderer
+pub(crate) struct ViewportTarget {
+    pub(crate) camera: ViewportCamera,
+    pub(crate) handle: ManualTextureViewHandle,
+    pub(crate) texture: Option<crate::target::TargetTextures>,
+    pub(crate) texture_handle: Option<TextureHandle>,
+    pub(crate) size: (u32, u32),
+    /// Virtual window mirroring this viewport's canvas
+    pub(crate) window: Entity,
+    /// Cursor, buttons and touches over this viewport's canvas
+    pub(crate) input: CanvasInputState,
+    /// Picking pointer of this viewport, apart from the main canvas mouse
+    pub(crate) pointer: CanvasPointer,
+}
+
+impl ViewportTarget {
+    pub(crate) fn new(world: &mut World, id: ViewportId, handle: ManualTextureViewHandle, camera: ViewportCamera) -> Self {
+        Self {
+            camera,
+            handle,
+            texture: None,
+            texture_handle: None,
+            size: (0, 0),
+            window: window::spawn_viewport_window(world),
+            input: CanvasInputState::default(),
+            pointer: CanvasPointer::spawn_custom(world, id),
+        }
+    }
+}
+
+/// Texture view handles of the viewports of one app
+///
+/// Handed out counting down from the one reserved for the main canvas, away
+/// from the low handles user code tends to pick, and reused once their viewport
+/// is removed, so they don't run down however many viewports come and go.
+/// Handles the app already has a texture view for are skipped.
+#[derive(Default)]
+pub(crate) struct ViewportHandles {
+    /// Handles given out so far, below `CANVAS_TEXTURE_VIEW`
+    allocated: u32,
+    free: Vec<ManualTextureViewHandle>,
+}
+
+impl ViewportHandles {
+    /// Take a handle no texture view of the app uses
+    pub(crate) fn allocate(&mut self, world: &World) -> ManualTextureViewHandle {
+        let views = world.get_resource::<ManualTextureViews>();
+        self.allocate_unused(|handle| views.is_some_and(|views| views.contains_key(&handle)))
+    }
+
+    /// Take a handle for which `taken` is false
+    fn allocate_unused(&mut self, taken: impl Fn(ManualTextureViewHandle) -> bool) -> ManualTextureViewHandle {
+        while let Some(handle) = self.free.pop() {
+            if !taken(handle) {
+                return handle;
+            }
+        }
+        loop {
+            self.allocated += 1;
+            let handle = ManualTextureViewHandle(CANVAS_TEXTURE_VIEW.0 - self.allocated);
+            if !taken(handle) {
+                return handle;
+            }
+        }
+    }
+
+    /// Give back the handle of a removed viewport
+    pub(crate) fn release(&mut self, handle: ManualTextureViewHandle) {
+        self.free.push(handle);
+    }
+}
+
+/// Point the viewport's camera at its texture view
+pub(crate) fn attach_camera(world: &mut World, target: &ViewportTarget) {
+    let Some(entity) = target.camera.resolve(world) else {
+        return;
+    };
+    if let Some(mut camera) = world.get_mut::<Camera>(entity) {
+        if !matches!(camera.target, RenderTarget::TextureView(handle) if handle == target.handle) {
+            camera.target = RenderTarget::TextureView(target.handle);
+        }
+    }
+}
+
+/// Give the viewport's camera back to the primary window
+///
+/// Only if it still renders into the viewport. The main canvas takes it back
+/// right away if it feeds the canvas.
+pub(crate) fn release_camera(world: &mut World, target: &ViewportTarget) {
+    let Some(entity) = target.camera.resolve(world) else {
+        return;
+    };
+    let Some(mut camera) = world.get_mut::<Camera>(entity) else {
+        return;
+    };
+    if !matches!(camera.target, RenderTarget::TextureView(handle) if handle == target.handle) {
+        return;
+    }
+    camera.target = RenderTarget::default();
+
+    let canvas = world.resource::<CanvasTarget>();
+    if let Some(handle) = canvas.view {
+        let window = canvas.window;
+        camera::attach_canvas_cameras(world, window, handle);
+    }
+}
+
+/// Update `ActiveViewport` for pointer input over a viewport
+fn track_active_viewport(world: &mut World, target: &ViewportTarget, event: &BevyInputEvent) {
+    let active = match event {
+        BevyInputEvent::CursorEntered | BevyInputEvent::CursorMoved { .. } => ActiveViewport {
+            camera: target.camera.resolve(world),
+            window: Some(target.window),
+        },
+        BevyInputEvent::CursorLeft => ActiveViewport::default(),
+        _ => return,
+    };
+    // Only touch the resource on change, for systems using `resource_changed`
+    world.resource_mut::<ActiveViewport>().set_if_neq(active);
+}
+
+/// Mirror the viewport's canvas onto its virtual window
+pub(crate) fn sync_viewport_window(
+    world: &mut World,
+    target: &mut ViewportTarget,
+    size: UVec2,
+    scale_factor: f32,
+) {
+    target.input.set_scale_factor(scale_factor);
+    window::sync_window(world, target.window, size, scale_factor, &target.input);
+}
+
+/// Write the input over a viewport's canvas for its pointer and window
+///
+/// Pointer capture is checked and released on the primary `canvas_window`.
+pub(crate) fn apply_input(
+    world: &mut World,
+    target: &mut ViewportTarget,
+    canvas_window: Entity,
+    event: BevyInputEvent,
+) {
+    let render_target = NormalizedRenderTarget::TextureView(target.handle);
+    target.pointer.apply(world, render_target, target.input.scale_factor(), &event);
+    track_active_viewport(world, target, &event);
+    target.input.apply_captured_by(world, target.window, canvas_window, event);
+}
+
+/// Paint source of a viewport canvas
+struct ManagedViewportPaintSource {
+    instance_id: BevyInstanceId,
+    viewport: ViewportId,
+    manager: Arc<Mutex<BevyInstanceManagerInner>>,
+}
+
+impl CustomPaintSource for ManagedViewportPaintSource {
+    fn resume(&mut self, device_handle: &DeviceHandle) {
+        // A viewport may be the first canvas of its app to get a device, or the
+        // first to see it change
+        self.manager.lock().unwrap().use_device(&self.instance_id, device_handle);
+
+        replay_pending(&self.manager, &self.instance_id);
+    }
+
+    fn suspend(&mut self) {}
+
+    fn render(
+        &mut self,
+        mut ctx: CustomPaintCtx<'_>,
+        width: u32,
+        height: u32,
+        scale: f64,
+    ) -> Option<TextureHandle> {
+        let mut mgr = self.manager.lock().unwrap();
+        mgr.unregister_evicted_textures(&mut ctx);
+        let instance = mgr.instances.get_mut(&self.instance_id)?;
+        instance.unregister_replaced_textures(&mut ctx);
+        let renderer = instance.renderer.as_mut()?;
+        renderer.render_viewport(ctx, self.viewport, width, height, scale)
+    }
+}
+
+impl BevyInstanceManager {
+    /// Register a viewport of an instance and the paint source of its canvas
+    fn add_viewport(
+        &self,
+        instance_id: BevyInstanceId,
+        viewport: ViewportId,
+        camera: ViewportCamera,
+        dioxus_renderer: &DioxusNativeWindowRenderer,
+    ) -> u64 {
+        self.window_renderer.get_or_init(|| dioxus_renderer.clone());
+        self.set_viewport_camera(&instance_id, viewport, camera);
+
+        let paint_source = ManagedViewportPaintSource {
+            instance_id,
+            viewport,
+            manager: self.inner.clone(),
+        };
+        dioxus_renderer.register_custom_paint_source(Box::new(paint_source))
+    }
+
+    /// Choose the camera shown by a viewport
+    ///
+    /// Kept by the manager, so renderers rebuilt later get the viewport too.
+    fn set_viewport_camera(&self, instance_id: &BevyInstanceId, viewport: ViewportId, camera: ViewportCamera) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        inner
+            .viewports
+            .entry(instance_id.clone())
+            .or_default()
+            .insert(viewport, camera.clone());
+        if let Some(renderer) = inner.instances.get_mut(instance_id).and_then(|i| i.renderer.as_mut()) {
+            renderer.handle_message(Box::new(ViewportMessage::Add { id: viewport, camera }));
+        }
+    }
+
+    /// Unregister a viewport and the paint source of its canvas
+    fn remove_viewport(&self, instance_id: &BevyInstanceId, viewport: ViewportId, paint_source_id: u64) {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let inner = &mut *inner;
+            if let Some(viewports) = inner.viewports.get_mut(instance_id) {
+                viewports.remove(&viewport);
+                if viewports.is_empty() {
+                    inner.viewports.remove(instance_id);
+                }
+            }
+            if let Some(renderer) = inner.instances.get_mut(instance_id).and_then(|i| i.renderer.as_mut()) {
+                renderer.handle_message(Box::new(ViewportMessage::Remove { id: viewport }));
+            }
+        }
+        if let Some(window_renderer) = self.window_renderer.get() {
+            window_renderer.unregister_custom_paint_source(paint_source_id);
+        }
+    }
+}
+
+/// Props for BevyViewport
+#[derive(Props, Clone)]
+pub struct BevyViewportProps {
+    /// Bevy app to show, shared with every component and viewport using the same id
+    #[props(into)]
+    pub instance_id: BevyInstanceId,
+
+    /// Camera of the app rendered into this viewport
+    #[props(into)]
+    pub camera: ViewportCamera,
+
+    /// Creates the app if no component has yet
+    ///
+    /// With a factory the viewport also keeps the app alive while mounted;
+    /// without one it only shows an app some other component holds.
+    #[props(default)]
+    pub factory: Option<RendererFactory>,
+}
+
+impl PartialEq for BevyViewportProps {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare only the app and camera, not the factory function
+        self.instance_id == other.instance_id && self.camera == other.camera
+    }
+}
+
+/// View of one camera of a shared Bevy app
+///
+/// Several viewports onto the same `instance_id` render the same world from
+/// different cameras, each into its own canvas with its own size and scale.
+/// Input over a viewport drives a picking pointer of its own for its camera and
+/// sets `ActiveViewport`; keyboard and mouse button input all reach the app.
+/// A new `instance_id` remounts the viewport onto the app of that id.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let level = BevyInstanceId::keyed("level");
+/// rsx! {
+///     BevyViewport { instance_id: level.clone(), camera: "top", factory: level_factory.clone() }
+///     BevyViewport { instance_id: level.clone(), camera: "front" }
+///     BevyViewport { instance_id: level.clone(), camera: "side" }
+///     BevyViewport { instance_id: level, camera: "perspective" }
+/// }
+/// ```
+#[component]
+pub fn BevyViewport(props: BevyViewportProps) -> Element {
+    // Rendered under the instance id, so a new id mounts a viewport of that app
+    let key = format!("{:?}", props.instance_id);
+    let BevyViewportProps {
+        instance_id,
+        camera,
+        factory,
+    } = props;
+    let body = rsx! {
+        BevyViewportBody { key: "{key}", instance_id, camera, factory }
+    };
+    keyed_body(body)
+}
+
+/// Body of a `BevyViewport`, for one instance
+#[component]
+fn BevyViewportBody(props: BevyViewportProps) -> Element {
+    let manager = use_instance_manager();
+
+    let renderer = use_context::<DioxusNativeWindowRenderer>();
+    let instance_id = props.instance_id.clone();
+    let viewport = use_hook(|| NEXT_VIEWPORT_ID.fetch_add(1, Ordering::Relaxed));
+
+    // Hold the app while mounted when this viewport may create it
+    use_hook_with_cleanup(
+        {
+            let instance_id = instance_id.clone();
+            let factory = props.factory.clone();
+            let renderer = renderer.clone();
+            move || {
+                let factory = factory?;
+                let mgr = manager.peek();
+                mgr.get_or_create(instance_id.clone(), &renderer, move |dev| factory(dev));
+                mgr.generation(&instance_id)
+            }
+        },
+        {
+            let instance_id = instance_id.clone();
+            move |generation| {
+                if let Some(generation) = generation {
+                    manager.peek().release_generation(&instance_id, generation);
+                }
+            }
+        },
+    );
+
+    let paint_source_id = use_hook_with_cleanup(
+        {
+            let instance_id = instance_id.clone();
+            let camera = props.camera.clone();
+            move || manager.peek().add_viewport(instance_id, viewport, camera, &renderer)
+        },
+        {
+            let instance_id = instance_id.clone();
+            move |paint_source_id| {
+                manager.peek().remove_viewport(&instance_id, viewport, paint_source_id);
+            }
+        },
+    );
+
+    // Switch cameras when the prop changes; the first camera was set when adding the viewport
+    let shown_camera = use_hook(|| std::rc::Rc::new(std::cell::RefCell::new(props.camera.clone())));
+    use_effect(use_reactive((&props.camera,), {
+        let instance_id = instance_id.clone();
+        move |(camera,)| {
+            if *shown_camera.borrow() != camera {
+                shown_camera.replace(camera.clone());
+                manager.peek().set_viewport_camera(&instance_id, viewport, camera);
+            }
+        }
+    }));
+
+    let cursor = cursor::use_canvas_cursor(manager, instance_id.clone());
+    let locked = cursor::use_pointer_locked(manager, instance_id.clone());
+
+    let send_input = move |event: BevyInputEvent| {
+        manager.peek().send_message(&instance_id, Box::new(ViewportInput { viewport, event }));
+    };
+
+    rsx! {
+        canvas::InputCanvas {
+            paint_source_id,
+            cursor: cursor(),
+            locked: locked(),
+            oninput: send_input,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{render, root_manager, unbuilt_factory};
+    use crate::BevyComponent;
+    use bevy::ecs::message::Messages;
+    use bevy::input::mouse::MouseMotion;
+    use bevy::math::Vec2;
+    use bevy::picking::pointer::PointerInput;
+    use bevy::window::{CursorMoved, Window, WindowBackendScaleFactorChanged, WindowFocused, WindowResized};
+
+    #[test]
+    fn sibling_viewports_show_the_components_instance() {
+        fn app() -> Element {
+            rsx! {
+                div {
+                    BevyComponent { instance_id: "level", factory: unbuilt_factory() }
+                }
+                div {
+                    BevyViewport { instance_id: "level", camera: "top" }
+                }
+                div {
+                    BevyViewport { instance_id: "level", camera: "front" }
+                }
+            }
+        }
+
+        let dom = render(app);
+        let inner = root_manager(&dom).inner;
+        let inner = inner.lock().unwrap();
+        let level = BevyInstanceId::keyed("level");
+        assert_eq!(inner.instances.len(), 1);
+        assert_eq!(inner.instances[&level].ref_count, 1);
+        let mut cameras: Vec<_> = inner.viewports[&level].values().cloned().collect();
+        cameras.sort_by_key(|camera| format!("{camera:?}"));
+        assert_eq!(cameras, vec![ViewportCamera::from("front"), ViewportCamera::from("top")]);
+    }
+
+    #[test]
+    fn viewports_report_input_in_their_own_windows() {
+        let mut world = World::new();
+        world.init_resource::<ActiveViewport>();
+        world.init_resource::<Messages<CursorMoved>>();
+        world.init_resource::<Messages<MouseMotion>>();
+        world.init_resource::<Messages<PointerInput>>();
+        world.init_resource::<Messages<WindowResized>>();
+        world.init_resource::<Messages<WindowBackendScaleFactorChanged>>();
+        world.init_resource::<Messages<WindowFocused>>();
+        let canvas_window = window::spawn_canvas_window(&mut world);
+        world.spawn((Camera::default(), Name::new("wide")));
+        let tall_camera = world.spawn((Camera::default(), Name::new("tall"))).id();
+
+        // A wide viewport on a 2x display and a tall one on a 1x display
+        let mut handles = ViewportHandles::default();
+        let (wide_handle, tall_handle) = (handles.allocate(&world), handles.allocate(&world));
+        let mut wide = ViewportTarget::new(&mut world, 0, wide_handle, "wide".into());
+        let mut tall = ViewportTarget::new(&mut world, 1, tall_handle, "tall".into());
+        sync_viewport_window(&mut world, &mut wide, UVec2::new(800, 200), 2.0);
+        sync_viewport_window(&mut world, &mut tall, UVec2::new(100, 300), 1.0);
+
+        // The same CSS position over each canvas
+        let position = Vec2::new(40.0, 60.0);
+        apply_input(&mut world, &mut wide, canvas_window, BevyInputEvent::CursorMoved { position });
+        apply_input(&mut world, &mut tall, canvas_window, BevyInputEvent::CursorMoved { position });
+        sync_viewport_window(&mut world, &mut wide, UVec2::new(800, 200), 2.0);
+        sync_viewport_window(&mut world, &mut tall, UVec2::new(100, 300), 1.0);
+
+        let moves: Vec<_> = world
+            .resource::<Messages<CursorMoved>>()
+            .iter_current_update_messages()
+            .map(|moved| (moved.window, moved.position))
+            .collect();
+        assert_eq!(moves, [(wide.window, Vec2::new(80.0, 120.0)), (tall.window, position)]);
+
+        let window = |entity| world.get::<Window>(entity).unwrap();
+        assert_eq!(window(wide.window).size(), Vec2::new(800.0, 200.0));
+        assert_eq!(window(wide.window).cursor_position(), Some(Vec2::new(80.0, 120.0)));
+        assert_eq!(window(tall.window).size(), Vec2::new(100.0, 300.0));
+        assert_eq!(window(tall.window).cursor_position(), Some(position));
+        // The canvas window saw none of it
+        assert_eq!(window(canvas_window).cursor_position(), None);
+
+        assert_eq!(
+            *world.resource::<ActiveViewport>(),
+            ActiveViewport {
+                camera: Some(tall_camera),
+                window: Some(tall.window),
+            }
+        );
+    }
+
+    #[test]
+    fn viewport_handles_are_reused_and_skip_taken_ones() {
+        let world = World::new();
+        let mut handles = ViewportHandles::default();
+        let first = handles.allocate(&world);
+        assert_eq!(first, ManualTextureViewHandle(CANVAS_TEXTURE_VIEW.0 - 1));
+
+        // Churning viewports keep getting the same handle
+        for _ in 0..1000 {
+            handles.release(first);
+            assert_eq!(handles.allocate(&world), first);
+        }
+
+        // Handles the app uses itself are never handed out, freed or new
+        let user = |handle: ManualTextureViewHandle| handle.0 >= CANVAS_TEXTURE_VIEW.0 - 2;
+        handles.release(first);
+        assert_eq!(handles.allocate_unused(user), ManualTextureViewHandle(CANVAS_TEXTURE_VIEW.0 - 3));
+    }
+}
@@ -0,0 +1,173 @@
+//! Virtual Bevy window
+//!
+//! The embed has no OS window, so anything in Bevy that expects one
+//! (`Camera::viewport_to_world`, picking, UI hit testing, cursor queries)
+//! would find nothing. A synthetic `Window` entity marked as `PrimaryWindow`
+//! stands in for the canvas and is kept in sync with it every frame.
+//!
+//! Cameras rendering into the canvas texture have a scale factor of 1, so the
+//! window overrides its scale factor to 1 as well: its logical pixels are the
+//! canvas' physical pixels, and `Window::cursor_position` can go straight into
+//! `Camera::viewport_to_world`. The display's scale factor is still reported as
+//! the backend scale factor, and in `CanvasViewport`.
+//!
+//! Each `BevyViewport` canvas gets a window of its own, marked `ViewportWindow`
+//! instead of `PrimaryWindow`, so input over it is reported in its own size.
+
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::resource::Resource;
+use bevy::ecs::world::World;
+use bevy::math::{UVec2, Vec2};
+use bevy::window::{
+    PrimaryWindow, Window, WindowBackendScaleFactorChanged, WindowFocused, WindowResized,
+    WindowResolution,
+};
+
+use crate::input::CanvasInputState;
+
+/// Marker for the synthetic window entity that mirrors the Bevy canvas
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct CanvasWindow;
+
+/// Marker for the synthetic window entity that mirrors a `BevyViewport` canvas
+///
+/// Input over the viewport is written for this window, so its
+/// `Window::cursor_position` is in the space of the viewport's camera. Pointer
+/// capture stays with the primary canvas window.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct ViewportWindow;
+
+/// Size and scale factor of the canvas the Bevy app renders into
+///
+/// Inserted by `BevyAppRenderer` and updated whenever the canvas is resized or
+/// moved to a display with a different scale factor. The render target is
+/// always sized in physical pixels.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CanvasViewport {
+    /// Canvas size in physical (device) pixels
+    pub physical_size: UVec2,
+    /// Canvas size in logical (CSS) pixels
+    pub logical_size: Vec2,
+    /// Ratio of physical to logical pixels
+    pub scale_factor: f32,
+}
+
+impl Default for CanvasViewport {
+    fn default() -> Self {
+        Self {
+            physical_size: UVec2::ZERO,
+            logical_size: Vec2::ZERO,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+/// Virtual window in the canvas texture's pixels
+fn virtual_window(title: &str) -> Window {
+    Window {
+        title: title.to_string(),
+        resolution: WindowResolution::default().with_scale_factor_override(1.0),
+        focused: false,
+        ..Default::default()
+    }
+}
+
+/// Spawn the virtual primary window standing in for the canvas
+pub(crate) fn spawn_canvas_window(world: &mut World) -> Entity {
+    world
+        .spawn((virtual_window("dioxus-bevy canvas"), PrimaryWindow, CanvasWindow))
+        .id()
+}
+
+/// Spawn the virtual window standing in for a viewport canvas
+pub(crate) fn spawn_viewport_window(world: &mut World) -> Entity {
+    world
+        .spawn((virtual_window("dioxus-bevy viewport"), ViewportWindow))
+        .id()
+}
+
+/// Mirror the canvas size, scale factor, focus and cursor position onto the
+/// virtual window and the `CanvasViewport` resource
+pub(crate) fn sync_canvas_window(
+    world: &mut World,
+    window_entity: Entity,
+    size: UVec2,
+    scale_factor: f32,
+    input: &CanvasInputState,
+) {
+    let viewport = CanvasViewport {
+        physical_size: size,
+        logical_size: size.as_vec2() / scale_factor,
+        scale_factor,
+    };
+    if world.get_resource::<CanvasViewport>() != Some(&viewport) {
+        world.insert_resource(viewport);
+    }
+
+    sync_window(world, window_entity, size, scale_factor, input);
+}
+
+/// Mirror a canvas' size, scale factor, focus and cursor position onto its
+/// virtual window
+///
+/// Writes `WindowResized`, `WindowBackendScaleFactorChanged` and `WindowFocused`
+/// messages when those change, as the windowing backend would with a scale
+/// factor override.
+pub(crate) fn sync_window(
+    world: &mut World,
+    window_entity: Entity,
+    size: UVec2,
+    scale_factor: f32,
+    input: &CanvasInputState,
+) {
+    let Some(mut window) = world.get_mut::<Window>(window_entity) else {
+        return;
+    };
+
+    let rescaled = window.resolution.base_scale_factor() != scale_factor;
+    if rescaled {
+        window.resolution.set_scale_factor(scale_factor);
+    }
+
+    let resized = size.x > 0
+        && size.y > 0
+        && (window.resolution.physical_width() != size.x
+            || window.resolution.physical_height() != size.y);
+    if resized {
+        window.resolution.set_physical_resolution(size.x, size.y);
+    }
+
+    let focus_changed = window.focused != input.focused();
+    if focus_changed {
+        window.focused = input.focused();
+    }
+
+    let cursor_position = input.cursor_position();
+    if window.physical_cursor_position() != cursor_position {
+        window.set_physical_cursor_position(cursor_position.map(|position| position.as_dvec2()));
+    }
+
+    let (width, height) = (window.width(), window.height());
+    let focused = window.focused;
+
+    if rescaled {
+        world.write_message(WindowBackendScaleFactorChanged {
+            window: window_entity,
+            scale_factor: scale_factor as f64,
+        });
+    }
+    if resized {
+        world.write_message(WindowResized {
+            window: window_entity,
+            width,
+            height,
+        });
+    }
+    if focus_changed {
+        world.write_message(WindowFocused {
+            window: window_entity,
+            focused,
+        });
+    }
+}
@@ -0,0 +1,59 @@
+//! `#[bevy_component]` components used from outside their module
+
+use dioxus::prelude::*;
+use dioxus_bevy::{BevyComponent, BevyRenderer, RendererFactory};
+use dioxus_native::DeviceHandle;
+use std::sync::Arc;
+
+mod scenes {
+    use bevy::prelude::*;
+    use dioxus::prelude::*;
+    use dioxus_bevy::bevy_component;
+
+    #[bevy_component]
+    pub fn empty_scene(app: &mut App) {
+        app.insert_resource(ClearColor(Color::BLACK));
+    }
+
+    #[bevy_component]
+    pub fn spinning_scene(app: &mut App, speed: ReadSignal<f32>, #[resource] tint: ReadSignal<u32>) {
+        app.insert_resource(ClearColor(Color::WHITE));
+    }
+
+    #[bevy_component]
+    pub fn gizmo_scene(app: &mut App, selected: Signal<u32>, #[bind] offset: Signal<f32>) {
+        app.insert_resource(ClearColor(Color::BLACK));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Hash)]
+struct Quality(u8);
+
+fn unbuilt_factory() -> RendererFactory {
+    Arc::new(|_: &DeviceHandle| -> Box<dyn BevyRenderer> { unreachable!("nothing is built") })
+}
+
+#[allow(dead_code)]
+fn panels() -> Element {
+    let speed = use_signal(|| 1.0_f32);
+    let tint = use_signal(|| 0_u32);
+    let selected = use_signal(|| 0_u32);
+    let offset = use_signal(|| 0.0_f32);
+    let quality = use_signal(|| Quality(2));
+    rsx! {
+        // Rebuild keys take plain hashable values
+        scenes::EmptyScene { rebuild_on: quality() }
+        BevyComponent { instance_id: "forest", factory: unbuilt_factory(), rebuild_on: "night" }
+        scenes::SpinningScene { instance_key: "spinner", speed, tint }
+        scenes::GizmoScene { selected, offset }
+    }
+}
+
+#[test]
+fn public_components_are_usable_from_other_modules() {
+    // Props are built outside the component's module, as `rsx!` does
+    let _props = scenes::EmptySceneProps::builder().instance_key("preview").build();
+    assert_eq!(scenes::SpinningSceneTint(3).0, 3);
+    // The prop marked `#[bind]` gets a resource
+    assert_eq!(scenes::GizmoSceneOffset(0.5).0, 0.5);
+}